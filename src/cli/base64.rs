@@ -2,11 +2,18 @@ use clap::{Parser, ValueEnum};
 use enum_dispatch::enum_dispatch;
 
 use crate::{
-    process::{process_decode, process_encode},
+    process::{
+        b64::{
+            process_datauri_decode, process_datauri_encode, process_pem_decode, process_pem_encode,
+        },
+        codec::{base64_codec, process_codec_encode},
+        get_input, process_decode,
+    },
+    utils::get_reader,
     CmdExector,
 };
 
-use super::verify_file;
+use super::{codec::write_decoded, verify_file};
 
 #[derive(Debug, Parser)]
 #[enum_dispatch(CmdExector)]
@@ -15,6 +22,80 @@ pub enum Base64SubCommand {
     Encode(Base64EncodeOpts),
     #[command(name = "decode", about = "Decode a base64 to string")]
     Decode(Base64DecodeOpts),
+    #[command(subcommand, name = "datauri", about = "Data URI encode & decode")]
+    DataUri(DataUriSubCommand),
+    #[command(subcommand, about = "PEM armor encode & decode")]
+    Pem(PemSubCommand),
+}
+
+#[derive(Debug, Parser)]
+#[enum_dispatch(CmdExector)]
+pub enum DataUriSubCommand {
+    #[command(about = "Encode a file to a `data:<mime>;base64,...` uri")]
+    Encode(DataUriEncodeOpts),
+    #[command(about = "Decode a data uri back to bytes")]
+    Decode(DataUriDecodeOpts),
+}
+
+#[derive(Debug, Parser)]
+#[enum_dispatch(CmdExector)]
+pub enum PemSubCommand {
+    #[command(about = "Wrap bytes in `-----BEGIN <LABEL>-----` armor")]
+    Encode(PemEncodeOpts),
+    #[command(about = "Unwrap the bytes of a PEM block")]
+    Decode(PemDecodeOpts),
+}
+
+#[derive(Debug, Parser)]
+pub struct DataUriEncodeOpts {
+    /// Specify the input file. If -, it means input from stdin
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+    /// MIME type of the data. If not specified, it's sniffed from the magic bytes
+    /// or the file extension
+    #[arg(long)]
+    pub mime: Option<String>,
+}
+
+#[derive(Debug, Parser)]
+pub struct DataUriDecodeOpts {
+    /// Specify the input file of the data uri. If -, it means input from stdin
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+    /// Write the decoded bytes to this file. If not specified, they are written to stdout
+    /// unmodified, or shown as text or a hexdump on a terminal.
+    #[arg(short, long)]
+    pub output: Option<String>,
+    /// Print the MIME type to stderr
+    #[arg(short, long, default_value_t = false)]
+    pub verbose: bool,
+}
+
+#[derive(Debug, Parser)]
+pub struct PemEncodeOpts {
+    /// Specify the input file. If -, it means input from stdin
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+    /// Label of the armor, e.g. `PRIVATE KEY`
+    #[arg(short, long)]
+    pub label: String,
+}
+
+#[derive(Debug, Parser)]
+pub struct PemDecodeOpts {
+    /// Specify the input file. If -, it means input from stdin
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+    /// Decode the first block with this label instead of the first block
+    #[arg(short, long)]
+    pub label: Option<String>,
+    /// Write the decoded bytes to this file. If not specified, they are written to stdout
+    /// unmodified, or shown as text or a hexdump on a terminal.
+    #[arg(short, long)]
+    pub output: Option<String>,
+    /// Print the label of the block to stderr
+    #[arg(short, long, default_value_t = false)]
+    pub verbose: bool,
 }
 
 #[derive(Debug, Parser)]
@@ -28,6 +109,9 @@ pub struct Base64EncodeOpts {
     /// Whether not use padding
     #[arg(long, default_value_t = false)]
     pub no_padding: bool,
+    /// Wrap lines at this many characters, 76 if no value is given (MIME), 64 for PEM
+    #[arg(long, num_args = 0..=1, default_missing_value = "76")]
+    pub wrap: Option<usize>,
 }
 
 #[derive(Debug, Parser)]
@@ -35,12 +119,19 @@ pub struct Base64DecodeOpts {
     /// Specify the input file. If -, it means input from stdin
     #[arg(short, long, value_parser = verify_file, default_value = "-")]
     pub input: String,
-    /// Decode format
-    #[arg(long, value_enum, default_value_t = Base64Format::Standard)]
-    pub format: Base64Format,
-    /// Whether not use padding
+    /// Decode format, `auto` detects the alphabet and padding
+    #[arg(long, value_enum, default_value_t = Base64DecodeFormat::Auto)]
+    pub format: Base64DecodeFormat,
+    /// Whether not use padding, ignored by `auto`
     #[arg(long, default_value_t = false)]
     pub no_padding: bool,
+    /// Print the detected base64 variant to stderr
+    #[arg(short, long, default_value_t = false)]
+    pub verbose: bool,
+    /// Write the decoded bytes to this file. If not specified, they are written to stdout
+    /// unmodified, or shown as text or a hexdump on a terminal.
+    #[arg(short, long)]
+    pub output: Option<String>,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
@@ -49,18 +140,83 @@ pub enum Base64Format {
     Urlsafe,
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum Base64DecodeFormat {
+    Auto,
+    Standard,
+    Urlsafe,
+}
+
 impl CmdExector for Base64DecodeOpts {
     async fn execute(self) -> anyhow::Result<()> {
-        let encoded = process_decode(&self.input, self.format, self.no_padding)?;
-        println!("\n{}", String::from_utf8(encoded)?);
+        let reader = get_reader(&self.input)?;
+        let variant = write_decoded(self.output.as_deref(), |writer| {
+            process_decode(reader, writer, self.format, self.no_padding)
+        })?;
+        if self.verbose {
+            let format = match variant.format {
+                Base64Format::Standard => "standard",
+                Base64Format::Urlsafe => "url-safe",
+            };
+            let padding = match variant.padding {
+                true => "padded",
+                false => "unpadded",
+            };
+            eprintln!("base64 variant: {format}, {padding}");
+        }
         Ok(())
     }
 }
 
 impl CmdExector for Base64EncodeOpts {
     async fn execute(self) -> anyhow::Result<()> {
-        let decoded = process_encode(&self.input, self.format, self.no_padding)?;
-        println!("\n{}", decoded);
+        let reader = get_reader(&self.input)?;
+        let codec = base64_codec(self.format, self.no_padding);
+        process_codec_encode(reader, std::io::stdout().lock(), codec, self.wrap)
+    }
+}
+
+impl CmdExector for DataUriEncodeOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let data = get_input(&self.input)?;
+        let uri = process_datauri_encode(&data, self.mime.as_deref(), Some(&self.input));
+        println!("{uri}");
+        Ok(())
+    }
+}
+
+impl CmdExector for DataUriDecodeOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let uri = String::from_utf8(get_input(&self.input)?)?;
+        let (mime, data) = process_datauri_decode(&uri)?;
+        write_output(&data, self.output.as_deref())?;
+        if self.verbose {
+            eprintln!("mime: {mime}");
+        }
+        Ok(())
+    }
+}
+
+impl CmdExector for PemEncodeOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let data = get_input(&self.input)?;
+        print!("{}", process_pem_encode(&data, &self.label)?);
         Ok(())
     }
 }
+
+impl CmdExector for PemDecodeOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let text = String::from_utf8(get_input(&self.input)?)?;
+        let (label, data) = process_pem_decode(&text, self.label.as_deref())?;
+        write_output(&data, self.output.as_deref())?;
+        if self.verbose {
+            eprintln!("label: {label}");
+        }
+        Ok(())
+    }
+}
+
+fn write_output(data: &[u8], output: Option<&str>) -> anyhow::Result<()> {
+    write_decoded(output, |writer| Ok(writer.write_all(data)?))
+}
@@ -0,0 +1,79 @@
+use clap::Parser;
+use enum_dispatch::enum_dispatch;
+
+use crate::{
+    process::cbor::{process_cbor_decode, process_cbor_diag, process_cbor_encode},
+    CmdExector,
+};
+
+use super::{codec::write_codec_output, verify_file, Codec, OutputFormat};
+
+#[derive(Debug, Parser)]
+#[enum_dispatch(CmdExector)]
+pub enum CborSubCommand {
+    #[command(about = "Decode CBOR into JSON or YAML")]
+    Decode(CborDecodeOpts),
+    #[command(about = "Show CBOR in diagnostic notation, with tags and byte strings")]
+    Diag(CborDiagOpts),
+    #[command(about = "Encode JSON as CBOR")]
+    Encode(CborEncodeOpts),
+}
+
+#[derive(Debug, Parser)]
+pub struct CborDecodeOpts {
+    /// Specify the input file. If -, it means input from stdin
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+    /// Codec of the input, e.g. hex or base64. If not specified, the input is raw CBOR
+    #[arg(short, long, value_enum)]
+    pub codec: Option<Codec>,
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Json)]
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, Parser)]
+pub struct CborDiagOpts {
+    /// Specify the input file. If -, it means input from stdin
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+    /// Codec of the input, e.g. hex or base64. If not specified, the input is raw CBOR
+    #[arg(short, long, value_enum)]
+    pub codec: Option<Codec>,
+}
+
+#[derive(Debug, Parser)]
+pub struct CborEncodeOpts {
+    /// Specify the JSON input file. If -, it means input from stdin
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+    /// Write the CBOR as text with this codec, e.g. hex or base64
+    #[arg(short, long, value_enum)]
+    pub codec: Option<Codec>,
+    /// Write the output to this file. If not specified, it's written to stdout, raw CBOR is
+    /// shown as a hexdump on a terminal.
+    #[arg(short, long)]
+    pub output: Option<String>,
+}
+
+impl CmdExector for CborDecodeOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let res = process_cbor_decode(&self.input, self.codec, self.format)?;
+        println!("{}", res.trim_end());
+        Ok(())
+    }
+}
+
+impl CmdExector for CborDiagOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        println!("{}", process_cbor_diag(&self.input, self.codec)?);
+        Ok(())
+    }
+}
+
+impl CmdExector for CborEncodeOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let data = process_cbor_encode(&self.input)?;
+        write_codec_output(&data, self.codec, self.output.as_deref())
+    }
+}
@@ -0,0 +1,117 @@
+use std::io::Write;
+
+use clap::{Parser, ValueEnum};
+
+use crate::{
+    process::codec::{codec_encode, process_codec_decode, process_codec_encode},
+    utils::{get_binary_writer, get_reader, print_bytes},
+    CmdExector,
+};
+
+use super::verify_file;
+
+#[derive(Debug, Parser)]
+pub struct EncodeOpts {
+    /// Specify the input file. If -, it means input from stdin
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+    /// Codec of the output
+    #[arg(short, long, value_enum)]
+    pub codec: Codec,
+    /// Wrap lines at this many characters, 76 if no value is given (MIME)
+    #[arg(long, num_args = 0..=1, default_missing_value = "76")]
+    pub wrap: Option<usize>,
+}
+
+#[derive(Debug, Parser)]
+pub struct DecodeOpts {
+    /// Specify the input file. If -, it means input from stdin
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+    /// Codec of the input
+    #[arg(short, long, value_enum)]
+    pub codec: Codec,
+    /// Write the decoded bytes to this file. If not specified, they are written to stdout
+    /// unmodified, or shown as text or a hexdump on a terminal.
+    #[arg(short, long)]
+    pub output: Option<String>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum Codec {
+    /// Lowercase hex, decoding accepts both cases
+    Hex,
+    /// Uppercase hex
+    HexUpper,
+    /// RFC 4648 base32 with padding
+    Base32,
+    /// Crockford's base32, case-insensitive and without padding
+    Base32Crockford,
+    /// Bitcoin base58
+    Base58,
+    /// Base62 with the `0-9A-Za-z` alphabet
+    Base62,
+    /// Standard base64 with padding
+    Base64,
+    /// Standard base64 without padding
+    Base64NoPad,
+    /// URL-safe base64 without padding
+    Base64Url,
+    /// URL-safe base64 with padding
+    Base64UrlPad,
+    /// Adobe Ascii85, decoding accepts the `<~ ~>` delimiters
+    Ascii85,
+    /// ZeroMQ Z85, the input length must be a multiple of 4
+    Z85,
+}
+
+impl CmdExector for EncodeOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let reader = get_reader(&self.input)?;
+        let writer = std::io::stdout().lock();
+        process_codec_encode(reader, writer, self.codec, self.wrap)
+    }
+}
+
+impl CmdExector for DecodeOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let reader = get_reader(&self.input)?;
+        write_decoded(self.output.as_deref(), |writer| {
+            process_codec_decode(reader, writer, self.codec)
+        })
+    }
+}
+
+/// Decode into the output file or a redirected stdout, or into a buffer shown with
+/// [`print_bytes`] if stdout is a terminal.
+pub(crate) fn write_decoded<T>(
+    output: Option<&str>,
+    decode: impl FnOnce(&mut dyn Write) -> anyhow::Result<T>,
+) -> anyhow::Result<T> {
+    match get_binary_writer(output)? {
+        Some(mut writer) => decode(&mut writer),
+        None => {
+            let mut decoded = Vec::new();
+            let res = decode(&mut decoded)?;
+            print_bytes(&decoded);
+            Ok(res)
+        }
+    }
+}
+
+/// Write binary output as text with the codec, or as is to the output file or stdout.
+pub(crate) fn write_codec_output(
+    data: &[u8],
+    codec: Option<Codec>,
+    output: Option<&str>,
+) -> anyhow::Result<()> {
+    if let Some(codec) = codec {
+        let encoded = codec_encode(data, codec)?;
+        match output {
+            Some(filename) => std::fs::write(filename, encoded + "\n")?,
+            None => println!("{}", encoded),
+        }
+        return Ok(());
+    }
+    write_decoded(output, |writer| Ok(writer.write_all(data)?))
+}
@@ -0,0 +1,138 @@
+use clap::{Parser, ValueEnum};
+use enum_dispatch::enum_dispatch;
+
+use crate::{
+    process::{
+        escape::{
+            process_html_escape, process_html_unescape, process_json_escape, process_json_unescape,
+            process_shell_escape, process_url_escape, process_url_unescape,
+        },
+        get_input,
+    },
+    CmdExector,
+};
+
+use super::verify_file;
+
+#[derive(Debug, Parser)]
+#[enum_dispatch(CmdExector)]
+pub enum EscapeSubCommand {
+    #[command(about = "Percent-encode or decode a url, url component or form value")]
+    Url(EscapeUrlOpts),
+    #[command(about = "Escape or unescape HTML entities")]
+    Html(EscapeHtmlOpts),
+    #[command(about = "Escape or unescape the content of a JSON string")]
+    Json(EscapeJsonOpts),
+    #[command(about = "Quote a string for POSIX shells")]
+    Shell(EscapeShellOpts),
+}
+
+#[derive(Debug, Parser)]
+pub struct EscapeUrlOpts {
+    /// Specify the input file. If -, it means input from stdin.
+    /// A trailing newline of the input is ignored
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+    /// What the input is part of
+    #[arg(long, value_enum, default_value_t = UrlMode::Component)]
+    pub mode: UrlMode,
+    /// Decode instead of encode
+    #[arg(short, long, default_value_t = false)]
+    pub decode: bool,
+}
+
+#[derive(Debug, Parser)]
+pub struct EscapeHtmlOpts {
+    /// Specify the input file. If -, it means input from stdin.
+    /// A trailing newline of the input is ignored
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+    /// Unescape instead of escape
+    #[arg(short, long, default_value_t = false)]
+    pub decode: bool,
+}
+
+#[derive(Debug, Parser)]
+pub struct EscapeJsonOpts {
+    /// Specify the input file. If -, it means input from stdin.
+    /// A trailing newline of the input is ignored
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+    /// Unescape instead of escape
+    #[arg(short, long, default_value_t = false)]
+    pub decode: bool,
+}
+
+#[derive(Debug, Parser)]
+pub struct EscapeShellOpts {
+    /// Specify the input file. If -, it means input from stdin.
+    /// A trailing newline of the input is ignored
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum UrlMode {
+    /// A path segment or query value, like `encodeURIComponent`
+    Component,
+    /// A whole url, like `encodeURI`
+    Full,
+    /// An `application/x-www-form-urlencoded` value, spaces become `+`
+    Form,
+}
+
+impl CmdExector for EscapeUrlOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let input = read_text(&self.input)?;
+        let res = match self.decode {
+            true => process_url_unescape(&input, self.mode)?,
+            false => process_url_escape(&input, self.mode),
+        };
+        println!("{res}");
+        Ok(())
+    }
+}
+
+impl CmdExector for EscapeHtmlOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let input = read_text(&self.input)?;
+        let res = match self.decode {
+            true => process_html_unescape(&input),
+            false => process_html_escape(&input),
+        };
+        println!("{res}");
+        Ok(())
+    }
+}
+
+impl CmdExector for EscapeJsonOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let input = read_text(&self.input)?;
+        let res = match self.decode {
+            true => process_json_unescape(&input)?,
+            false => process_json_escape(&input)?,
+        };
+        println!("{res}");
+        Ok(())
+    }
+}
+
+impl CmdExector for EscapeShellOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let input = read_text(&self.input)?;
+        println!("{}", process_shell_escape(&input));
+        Ok(())
+    }
+}
+
+/// Read the input as UTF-8 without the trailing newline that `echo` and editors add.
+fn read_text(input: &str) -> anyhow::Result<String> {
+    let mut text = String::from_utf8(get_input(input)?)?;
+    if text.ends_with('\n') {
+        text.pop();
+        if text.ends_with('\r') {
+            text.pop();
+        }
+    }
+    Ok(text)
+}
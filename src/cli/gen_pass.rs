@@ -1,11 +1,96 @@
-use clap::Parser;
+use std::fs;
+
+use anyhow::anyhow;
+use clap::{Args, Parser, ValueEnum};
 use colored::*;
-use zxcvbn::zxcvbn;
+use enum_dispatch::enum_dispatch;
 
-use crate::{process, CmdExector};
+use super::verify_file;
+use crate::{
+    process::{
+        self,
+        gen_pass::{
+            find_forbidden, genphrase_entropy, genpronounceable_entropy, load_wordlist,
+            process_genpass_with_policy, process_genpronounceable, CharClassKind, GenPassPolicy,
+            PasswordReport,
+        },
+        get_input,
+        pass_check::PasswordAnalysis,
+        process_genpass_derive, process_pass_check,
+        token::{process_gen_token, process_verify_token, token_hash},
+    },
+    utils::read_secret,
+    CmdExector,
+};
 
 #[derive(Debug, Parser)]
+#[command(args_conflicts_with_subcommands = true)]
 pub struct GenPassOpts {
+    #[command(subcommand)]
+    pub cmd: Option<GenPassSubCommand>,
+
+    #[command(flatten)]
+    pub rules: GenPassRulesArgs,
+
+    /// Generate a memorable passphrase made of words instead of random characters.
+    /// A digit and a symbol are injected unless `--no-number` and `--no-symbol` are set.
+    #[arg(long, default_value_t = false, conflicts_with_all = [
+        "length", "no_uppercase", "no_lowercase",
+        "charset", "exclude", "include_ambiguous", "symbols",
+        "min_upper", "min_lower", "min_digits", "min_symbols",
+    ])]
+    pub passphrase: bool,
+
+    /// Generate a pronounceable password made of consonant-vowel syllables.
+    /// It's easier to type and read out, but weaker than a random one of the same length.
+    #[arg(long, default_value_t = false, conflicts_with_all = [
+        "passphrase", "charset", "exclude", "include_ambiguous", "symbols",
+        "min_upper", "min_lower", "min_digits", "min_symbols",
+    ])]
+    pub pronounceable: bool,
+
+    /// Number of words in the passphrase.
+    #[arg(long, default_value_t = 6, requires = "passphrase")]
+    pub words: u8,
+
+    /// Separator between words of the passphrase.
+    #[arg(long, default_value = "-", requires = "passphrase")]
+    pub separator: String,
+
+    /// Capitalization of words of the passphrase.
+    #[arg(long, value_enum, default_value_t = WordCase::Lower, requires = "passphrase")]
+    pub case: WordCase,
+
+    /// Wordlist file for the passphrase, one word per line or EFF diceware format.
+    /// If not specified, the builtin wordlist is used.
+    #[arg(long, value_parser = verify_file, requires = "passphrase")]
+    pub wordlist: Option<String>,
+
+    /// Number of passwords to generate.
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u16).range(1..))]
+    pub count: u16,
+
+    /// Regenerate until the zxcvbn score of the password reaches this value.
+    #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=4))]
+    pub min_score: u8,
+
+    /// Maximum attempts to generate a password that satisfies `--min-score` and `--forbid`.
+    #[arg(long, default_value_t = 100, value_parser = clap::value_parser!(u32).range(1..))]
+    pub max_attempts: u32,
+
+    /// Wordlist files of words that must not appear in the password, one word per line,
+    /// e.g. company or product names. They also lower the zxcvbn score.
+    #[arg(long, value_parser = verify_file)]
+    pub forbid: Vec<String>,
+
+    /// Print the passwords and their strength reports in JSON.
+    #[arg(long, default_value_t = false)]
+    pub json: bool,
+}
+
+/// Rules of the characters that a password is made of.
+#[derive(Debug, Args)]
+pub struct GenPassRulesArgs {
     #[arg(short, long, default_value_t = 16)]
     pub length: u8,
 
@@ -20,23 +105,437 @@ pub struct GenPassOpts {
 
     #[arg(long, default_value_t = false)]
     pub no_symbol: bool,
+
+    /// Only pick characters from this set, still grouped by the classes above.
+    #[arg(long, allow_hyphen_values = true)]
+    pub charset: Option<String>,
+
+    /// Characters that must never appear in the password.
+    #[arg(long, allow_hyphen_values = true)]
+    pub exclude: Option<String>,
+
+    /// Include ambiguous characters like `I`, `O`, `l` and `0`.
+    #[arg(long, default_value_t = false, conflicts_with = "charset")]
+    pub include_ambiguous: bool,
+
+    /// Symbols to pick from, instead of the builtin `!@#$%^&*_`.
+    #[arg(long, allow_hyphen_values = true)]
+    pub symbols: Option<String>,
+
+    /// Minimum count of uppercase letters.
+    #[arg(long)]
+    pub min_upper: Option<u8>,
+
+    /// Minimum count of lowercase letters.
+    #[arg(long)]
+    pub min_lower: Option<u8>,
+
+    /// Minimum count of digits.
+    #[arg(long)]
+    pub min_digits: Option<u8>,
+
+    /// Minimum count of symbols.
+    #[arg(long)]
+    pub min_symbols: Option<u8>,
+}
+
+#[derive(Debug, Parser)]
+#[enum_dispatch(CmdExector)]
+pub enum GenPassSubCommand {
+    #[command(about = "Analyze the strength of existing passwords read from stdin")]
+    Check(GenPassCheckOpts),
+    #[command(
+        about = "Derive a reproducible site password from a master passphrase read from stdin"
+    )]
+    Derive(GenPassDeriveOpts),
+    #[command(about = "Generate a prefixed API token with an embedded checksum")]
+    Token(GenPassTokenOpts),
+}
+
+#[derive(Debug, Parser)]
+#[enum_dispatch(CmdExector)]
+pub enum GenPassTokenSubCommand {
+    #[command(about = "Validate the checksum of a token offline")]
+    Verify(GenPassTokenVerifyOpts),
+}
+
+#[derive(Debug, Parser)]
+pub struct GenPassCheckOpts {
+    /// Read one password per line until EOF instead of a single password.
+    #[arg(long, default_value_t = false)]
+    pub batch: bool,
+
+    /// Wordlist files of words like company names that make the password weaker, one word per line.
+    #[arg(long, value_parser = verify_file)]
+    pub forbid: Vec<String>,
+
+    /// Print the analysis in JSON.
+    #[arg(long, default_value_t = false)]
+    pub json: bool,
+}
+
+#[derive(Debug, Parser)]
+pub struct GenPassDeriveOpts {
+    /// Site the password is used for, e.g. `example.com`.
+    #[arg(long)]
+    pub site: String,
+
+    /// Login on the site, e.g. an email or username.
+    #[arg(long, default_value = "")]
+    pub login: String,
+
+    /// Increase it to rotate the password of the site.
+    #[arg(long, default_value_t = 1)]
+    pub counter: u32,
+
+    #[command(flatten)]
+    pub rules: GenPassRulesArgs,
+}
+
+#[derive(Debug, Parser)]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct GenPassTokenOpts {
+    #[command(subcommand)]
+    pub cmd: Option<GenPassTokenSubCommand>,
+
+    /// Prefix identifying the token type, made of ASCII letters, digits and `_`.
+    #[arg(long, default_value = "rcli_live_")]
+    pub prefix: String,
+
+    /// Number of random base62 characters, without prefix and checksum.
+    #[arg(short, long, default_value_t = 30, value_parser = clap::value_parser!(u8).range(16..))]
+    pub length: u8,
+
+    /// Also print the BLAKE3 hash of the token, to store instead of the token itself.
+    #[arg(long, default_value_t = false)]
+    pub hash: bool,
+}
+
+#[derive(Debug, Parser)]
+pub struct GenPassTokenVerifyOpts {
+    /// The token to verify. If not specified, it's read from stdin.
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum WordCase {
+    Lower,
+    Upper,
+    Title,
+    Random,
 }
 
 impl CmdExector for GenPassOpts {
     async fn execute(self) -> anyhow::Result<()> {
-        let password = process::gen_pass::process_genpass(
+        if let Some(cmd) = self.cmd {
+            return cmd.execute().await;
+        }
+        // only passphrases are made of words
+        let wordlist = match (self.passphrase, &self.wordlist) {
+            (false, _) => Vec::new(),
+            (true, Some(path)) => load_wordlist(Some(&fs::read_to_string(path)?), self.case),
+            (true, None) => load_wordlist(None, self.case),
+        };
+        let policy = self.rules.policy()?;
+        if !self.passphrase && !self.pronounceable {
+            policy.validate()?;
+        }
+        let forbidden = read_wordlists(&self.forbid)?;
+        let forbidden: Vec<&str> = forbidden.iter().map(String::as_str).collect();
+
+        let mut reports = Vec::with_capacity(self.count as usize);
+        for _ in 0..self.count {
+            reports.push(self.generate_report(&wordlist, &policy, &forbidden)?);
+        }
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&reports)?);
+            return Ok(());
+        }
+        for report in reports {
+            println!(
+                "Successfully generate password: {}, strength score is: {}, entropy is: {}{} bits",
+                report.password.bright_cyan(),
+                report.score.to_string().red(),
+                if report.entropy_upper_bound {
+                    "at most "
+                } else {
+                    ""
+                },
+                format!("{:.1}", report.entropy).yellow(),
+            );
+        }
+        Ok(())
+    }
+}
+
+impl CmdExector for GenPassCheckOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let forbidden = read_wordlists(&self.forbid)?;
+        let forbidden: Vec<&str> = forbidden.iter().map(String::as_str).collect();
+
+        // passwords and their line numbers, blank lines of a batch are skipped
+        let passwords: Vec<(usize, String)> = if self.batch {
+            let input = String::from_utf8(get_input("-")?)?;
+            input
+                .lines()
+                .map(|line| line.trim_end_matches('\r'))
+                .enumerate()
+                .filter(|(_, line)| !line.trim().is_empty())
+                .map(|(i, line)| (i + 1, line.to_string()))
+                .collect()
+        } else {
+            vec![(1, read_secret("Password: ")?)]
+        };
+
+        let mut analyses = Vec::with_capacity(passwords.len());
+        for (line, password) in &passwords {
+            if password.is_empty() {
+                return Err(anyhow!("password at line {line} is empty"));
+            }
+            analyses.push(process_pass_check(password, &forbidden)?);
+        }
+
+        if self.json {
+            match self.batch {
+                true => println!("{}", serde_json::to_string_pretty(&analyses)?),
+                false => println!("{}", serde_json::to_string_pretty(&analyses[0])?),
+            }
+            return Ok(());
+        }
+        for ((line, _), analysis) in passwords.iter().zip(&analyses) {
+            if self.batch {
+                println!("{}", format!("Password at line {line}").bold());
+            }
+            print_analysis(analysis);
+        }
+        Ok(())
+    }
+}
+
+impl CmdExector for GenPassDeriveOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let policy = self.rules.policy()?;
+        policy.validate()?;
+        let master = read_secret("Master passphrase: ")?;
+        let password =
+            process_genpass_derive(&master, &self.site, &self.login, self.counter, &policy)?;
+        println!("Successfully derive password: {}", password.bright_cyan());
+        Ok(())
+    }
+}
+
+impl CmdExector for GenPassTokenOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        if let Some(cmd) = self.cmd {
+            return cmd.execute().await;
+        }
+        let token = process_gen_token(&self.prefix, self.length)?;
+        println!("{}", token.bright_cyan());
+        if self.hash {
+            println!("blake3: {}", token_hash(&token).purple());
+        }
+        Ok(())
+    }
+}
+
+impl CmdExector for GenPassTokenVerifyOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let token = match self.token {
+            Some(token) => token,
+            None => read_secret("Token: ")?,
+        };
+        match process_verify_token(token.trim()) {
+            true => println!("{}", "√ Token checksum verified".green()),
+            false => println!("{}", "x Token checksum not verified".red()),
+        }
+        Ok(())
+    }
+}
+
+fn print_analysis(analysis: &PasswordAnalysis) {
+    let score = format!("{}/4", analysis.score);
+    let score = match analysis.score {
+        0 | 1 => score.red(),
+        2 => score.yellow(),
+        _ => score.green(),
+    };
+    println!("  strength score: {score}");
+    println!(
+        "  length: {}, classes: {}",
+        analysis.length,
+        analysis.classes.join(", ")
+    );
+    println!(
+        "  estimated guesses: {} (10^{:.2})",
+        analysis.guesses, analysis.guesses_log10
+    );
+    println!("  crack time:");
+    let times = &analysis.crack_time;
+    println!(
+        "    online, throttled (100/h): {}",
+        times.online_throttling.purple()
+    );
+    println!(
+        "    online, unthrottled (10/s): {}",
+        times.online_no_throttling.purple()
+    );
+    println!(
+        "    offline, slow hashing (1e4/s): {}",
+        times.offline_slow_hashing.purple()
+    );
+    println!(
+        "    offline, fast hashing (1e10/s): {}",
+        times.offline_fast_hashing.purple()
+    );
+    println!("  patterns:");
+    for m in &analysis.patterns {
+        println!("    {} `{}` {}", m.pattern.cyan(), m.token, m.detail);
+    }
+    if let Some(warning) = &analysis.warning {
+        println!("  warning: {}", warning.red());
+    }
+    if !analysis.suggestions.is_empty() {
+        println!("  suggestions:");
+        for suggestion in &analysis.suggestions {
+            println!("    - {suggestion}");
+        }
+    }
+}
+
+impl GenPassRulesArgs {
+    /// Build the character policy of the password from the options.
+    fn policy(&self) -> anyhow::Result<GenPassPolicy> {
+        let mut policy = GenPassPolicy::new(
             self.length,
             !self.no_uppercase,
             !self.no_lowercase,
             !self.no_number,
             !self.no_symbol,
-        )?;
-        let score = zxcvbn(&password, &[]).unwrap().score();
-        println!(
-            "Successfully generate password: {}, strength score is: {}",
-            password.bright_cyan(),
-            score.to_string().red(),
         );
-        Ok(())
+        if let Some(charset) = &self.charset {
+            policy.set_charset(charset)?;
+        }
+        if let Some(symbols) = &self.symbols {
+            policy.set_symbols(symbols)?;
+        }
+        if self.include_ambiguous {
+            policy.include_ambiguous();
+        }
+        if let Some(exclude) = &self.exclude {
+            policy.exclude(exclude);
+        }
+        for (kind, min) in [
+            (CharClassKind::Upper, self.min_upper),
+            (CharClassKind::Lower, self.min_lower),
+            (CharClassKind::Number, self.min_digits),
+            (CharClassKind::Symbol, self.min_symbols),
+        ] {
+            if let Some(min) = min {
+                policy.set_min(kind, min)?;
+            }
+        }
+        Ok(policy)
+    }
+}
+
+/// Read the words of wordlist files, one word per line.
+fn read_wordlists(paths: &[String]) -> anyhow::Result<Vec<String>> {
+    let mut words = Vec::new();
+    for path in paths {
+        let content = fs::read_to_string(path)?;
+        words.extend(
+            content
+                .lines()
+                .map(str::trim)
+                .filter(|word| !word.is_empty())
+                .map(String::from),
+        );
+    }
+    Ok(words)
+}
+
+impl GenPassOpts {
+    /// Generate a password that satisfies `--min-score` and `--forbid`,
+    /// giving up after `--max-attempts` attempts.
+    fn generate_report(
+        &self,
+        wordlist: &[String],
+        policy: &GenPassPolicy,
+        forbidden: &[&str],
+    ) -> anyhow::Result<PasswordReport> {
+        let mut best_score = None;
+        for _ in 0..self.max_attempts {
+            let (password, entropy) = self.generate(wordlist, policy)?;
+            if find_forbidden(&password, forbidden).is_some() {
+                continue;
+            }
+            let separator = match self.passphrase {
+                true => self.separator.as_str(),
+                false => "",
+            };
+            let mut report = PasswordReport::new(password, entropy, forbidden, separator)?;
+            report.entropy_upper_bound = self.min_score > 0 || !forbidden.is_empty();
+            if report.score >= self.min_score {
+                return Ok(report);
+            }
+            best_score = best_score.max(Some(report.score));
+        }
+        let best_score = match best_score {
+            Some(score) => format!("the best score is {score}"),
+            None => "all of them contain forbidden words".to_string(),
+        };
+        Err(anyhow!(
+            "failed to generate a password with score >= {} in {} attempts, {best_score}. Try a longer length or more character classes",
+            self.min_score,
+            self.max_attempts,
+        ))
+    }
+
+    /// Generate a password and its entropy bits according to the options.
+    fn generate(
+        &self,
+        wordlist: &[String],
+        policy: &GenPassPolicy,
+    ) -> anyhow::Result<(String, f64)> {
+        if self.passphrase {
+            let password = process::gen_pass::process_genphrase(
+                wordlist,
+                self.words,
+                &self.separator,
+                self.case,
+                !self.rules.no_number,
+                !self.rules.no_symbol,
+            )?;
+            let entropy = genphrase_entropy(
+                wordlist.len(),
+                self.words,
+                self.case,
+                !self.rules.no_number,
+                !self.rules.no_symbol,
+            );
+            Ok((password, entropy))
+        } else if self.pronounceable {
+            let rules = &self.rules;
+            let password = process_genpronounceable(
+                rules.length,
+                !rules.no_uppercase,
+                !rules.no_lowercase,
+                !rules.no_number,
+                !rules.no_symbol,
+            )?;
+            let entropy = genpronounceable_entropy(
+                rules.length,
+                !rules.no_uppercase,
+                !rules.no_lowercase,
+                !rules.no_number,
+                !rules.no_symbol,
+            );
+            Ok((password, entropy))
+        } else {
+            let password = process_genpass_with_policy(policy)?;
+            let entropy = policy.entropy();
+            Ok((password, entropy))
+        }
     }
 }
@@ -0,0 +1,98 @@
+use std::io::{IsTerminal, Write};
+
+use clap::Parser;
+use enum_dispatch::enum_dispatch;
+
+use crate::{
+    process::{
+        get_input,
+        hex::{process_hex_dump, process_hex_reverse, HexDumpOptions},
+    },
+    utils::{get_binary_writer, get_reader, print_bytes},
+    CmdExector,
+};
+
+use super::verify_file;
+
+#[derive(Debug, Parser)]
+#[enum_dispatch(CmdExector)]
+pub enum HexSubCommand {
+    #[command(about = "Show offset, hex and ASCII columns like xxd, or reverse a hexdump")]
+    Dump(HexDumpOpts),
+}
+
+#[derive(Debug, Parser)]
+pub struct HexDumpOpts {
+    /// Specify the input file. If -, it means input from stdin
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+    /// Bytes per line
+    #[arg(short = 'c', long, default_value_t = 16, value_parser = clap::value_parser!(u16).range(1..=256))]
+    pub width: u16,
+    /// Bytes per group of hex digits, 0 for no grouping
+    #[arg(short, long, default_value_t = 2)]
+    pub group: u16,
+    /// Start at this offset, in decimal or `0x` hex
+    #[arg(short = 's', long, default_value = "0", value_parser = parse_offset)]
+    pub offset: u64,
+    /// Dump at most this many bytes, in decimal or `0x` hex
+    #[arg(short = 'n', long, value_parser = parse_offset)]
+    pub length: Option<u64>,
+    /// Never color the output, it's only colored on a terminal
+    #[arg(long, default_value_t = false)]
+    pub no_color: bool,
+    /// Turn a hexdump (or plain hex) back into binary
+    #[arg(short, long, default_value_t = false, conflicts_with_all = ["offset", "length"])]
+    pub reverse: bool,
+    /// Write the binary of `--reverse` to this file. If not specified, it's written to stdout
+    /// unmodified, or shown as text or a hexdump on a terminal.
+    #[arg(short, long, requires = "reverse")]
+    pub output: Option<String>,
+}
+
+impl CmdExector for HexDumpOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        if self.reverse {
+            let text = String::from_utf8(get_input(&self.input)?)?;
+            let data = process_hex_reverse(&text)?;
+            match get_binary_writer(self.output.as_deref())? {
+                Some(mut writer) => writer.write_all(&data)?,
+                None => print_bytes(&data),
+            }
+            return Ok(());
+        }
+        let stdout = std::io::stdout();
+        let opts = HexDumpOptions {
+            width: self.width as usize,
+            group: self.group as usize,
+            color: !self.no_color && stdout.is_terminal(),
+        };
+        let reader = get_reader(&self.input)?;
+        let writer = std::io::BufWriter::new(stdout.lock());
+        process_hex_dump(reader, writer, self.offset, self.length, &opts)
+    }
+}
+
+fn parse_offset(value: &str) -> Result<u64, String> {
+    let res = match value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+    {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => value.parse(),
+    };
+    res.map_err(|e| format!("invalid offset `{value}`: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_offset() {
+        assert_eq!(parse_offset("32"), Ok(32));
+        assert_eq!(parse_offset("0x20"), Ok(32));
+        assert!(parse_offset("0xzz").is_err());
+        assert!(parse_offset("-1").is_err());
+    }
+}
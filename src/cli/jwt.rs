@@ -6,6 +6,7 @@ use crate::{
     process::{
         get_input,
         jwt::{process_jwt_sign, process_jwt_verify},
+        time::parse_duration,
     },
     utils::print_verify_result,
     CmdExector,
@@ -32,7 +33,8 @@ pub struct JwtSignOpts {
     #[arg(long)]
     pub aud: String,
     // Expiration Time, default time unit is `s`.
-    // Support time unit like `s(second)`, `m(minute)`, `h(hour)`, `d(day)`.
+    // Support time unit like `s(second)`, `m(minute)`, `h(hour)`, `d(day)`, `w(week)`,
+    // and ISO 8601 durations like `P1DT2H`.
     #[arg(long, required = false, value_parser = parse_exp, default_value_t = 7200)]
     pub exp: u64,
 }
@@ -48,35 +50,8 @@ pub struct JwtVerifyOpts {
 }
 
 fn parse_exp(duration: &str) -> Result<u64, String> {
-    let duration = duration.to_lowercase();
-    let mut total_second: u64 = 0;
-    let mut current_number = String::new();
-    for ch in duration.chars() {
-        if ch.is_ascii_digit() {
-            current_number.push(ch);
-        } else {
-            let multiplier = match ch {
-                's' => 1,
-                'm' => 60,
-                'h' => 60 * 60,
-                'd' => 24 * 60 * 60,
-                _ => return Err("invalid exp".to_string()),
-            };
-            let value: u64 = current_number
-                .parse()
-                .map_err(|e| format!("{}", e).to_string())?;
-            total_second += value * multiplier;
-            current_number.clear();
-        }
-    }
-
-    if !current_number.is_empty() {
-        let value: u64 = current_number
-            .parse()
-            .map_err(|e| format!("{}", e).to_string())?;
-        total_second += value;
-    }
-    Ok(total_second)
+    let duration = parse_duration(duration).map_err(|e| e.to_string())?;
+    u64::try_from(duration.num_seconds()).map_err(|_| "exp must not be negative".to_string())
 }
 
 impl CmdExector for JwtSignOpts {
@@ -115,6 +90,8 @@ mod tests {
         assert_eq!(parse_exp("1s1h1d")?, 1 + 3600 + 3600 * 24);
         assert!(parse_exp("1asa").is_err());
         assert!(parse_exp("xxx").is_err());
+        assert_eq!(parse_exp("PT2H")?, 7200);
+        assert!(parse_exp("-1h").is_err());
         Ok(())
     }
 }
@@ -1,8 +1,16 @@
 pub mod base64;
+pub mod cbor;
+pub mod codec;
 pub mod csv;
+pub mod escape;
 pub mod gen_pass;
+pub mod hex;
 pub mod http;
 pub mod jwt;
+pub mod msgpack;
+pub mod otp;
+pub mod passwd;
+pub mod proto;
 pub mod text;
 pub mod time;
 
@@ -12,10 +20,18 @@ use enum_dispatch::enum_dispatch;
 use std::path::{Path, PathBuf};
 
 pub use self::base64::*;
+pub use self::cbor::*;
+pub use self::codec::*;
 pub use self::csv::*;
+pub use self::escape::*;
 pub use self::gen_pass::*;
+pub use self::hex::*;
 pub use self::http::*;
 pub use self::jwt::*;
+pub use self::msgpack::*;
+pub use self::otp::*;
+pub use self::passwd::*;
+pub use self::proto::*;
 pub use self::text::*;
 pub use self::time::*;
 
@@ -24,6 +40,11 @@ pub use self::time::*;
 pub struct Opts {
     #[command(subcommand)]
     pub cmd: SubCommand,
+
+    /// INSECURE: seed every random value, e.g. keys, nonces, salts and passwords,
+    /// so the output is reproducible. Only meant for golden-file tests.
+    #[arg(long, global = true, hide = true)]
+    pub seed: Option<u64>,
 }
 
 /// rcli csv -i input.csv -o output.json --header -d ','
@@ -33,9 +54,23 @@ pub enum SubCommand {
     #[command(name = "csv", about = "Show CSV, or convert CSV to other formats")]
     Csv(CsvOpts),
     #[command(name = "genpass", about = "Generate a password")]
-    GenPass(GenPassOpts),
+    GenPass(Box<GenPassOpts>),
     #[command(subcommand, about = "Base64 encode & decode")]
     Base64(Base64SubCommand),
+    #[command(about = "Encode with hex, base32, base58, base62, base64 or base85")]
+    Encode(EncodeOpts),
+    #[command(about = "Decode hex, base32, base58, base62, base64 or base85")]
+    Decode(DecodeOpts),
+    #[command(subcommand, about = "URL, HTML, JSON & shell escaping")]
+    Escape(EscapeSubCommand),
+    #[command(subcommand, about = "Hexdump & reverse")]
+    Hex(HexSubCommand),
+    #[command(subcommand, about = "CBOR to JSON/YAML, diagnostic notation & back")]
+    Cbor(CborSubCommand),
+    #[command(subcommand, about = "MessagePack to JSON/YAML & back")]
+    Msgpack(MsgpackSubCommand),
+    #[command(subcommand, about = "Protobuf decode, with or without a schema")]
+    Proto(ProtoSubCommand),
     #[command(about = "Time utils")]
     Time(TimeOpts),
     #[command(subcommand, about = "Text sign & verify")]
@@ -44,6 +79,10 @@ pub enum SubCommand {
     Http(HttpSubCommand),
     #[command(subcommand, about = "Jwt sign & verify")]
     Jwt(JwtSubCommand),
+    #[command(subcommand, about = "TOTP & HOTP one-time passwords")]
+    Otp(OtpSubCommand),
+    #[command(subcommand, about = "Password hash & verify")]
+    Passwd(PasswdSubCommand),
 }
 
 fn verify_file(filename: &str) -> Result<String, &'static str> {
@@ -0,0 +1,60 @@
+use clap::Parser;
+use enum_dispatch::enum_dispatch;
+
+use crate::{
+    process::msgpack::{process_msgpack_decode, process_msgpack_encode},
+    CmdExector,
+};
+
+use super::{codec::write_codec_output, verify_file, Codec, OutputFormat};
+
+#[derive(Debug, Parser)]
+#[enum_dispatch(CmdExector)]
+pub enum MsgpackSubCommand {
+    #[command(about = "Decode MessagePack into JSON or YAML")]
+    Decode(MsgpackDecodeOpts),
+    #[command(about = "Encode JSON as MessagePack")]
+    Encode(MsgpackEncodeOpts),
+}
+
+#[derive(Debug, Parser)]
+pub struct MsgpackDecodeOpts {
+    /// Specify the input file. If -, it means input from stdin
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+    /// Codec of the input, e.g. hex or base64. If not specified, the input is raw MessagePack
+    #[arg(short, long, value_enum)]
+    pub codec: Option<Codec>,
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Json)]
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, Parser)]
+pub struct MsgpackEncodeOpts {
+    /// Specify the JSON input file. If -, it means input from stdin
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+    /// Write the MessagePack as text with this codec, e.g. hex or base64
+    #[arg(short, long, value_enum)]
+    pub codec: Option<Codec>,
+    /// Write the output to this file. If not specified, it's written to stdout, raw
+    /// MessagePack is shown as a hexdump on a terminal.
+    #[arg(short, long)]
+    pub output: Option<String>,
+}
+
+impl CmdExector for MsgpackDecodeOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let res = process_msgpack_decode(&self.input, self.codec, self.format)?;
+        println!("{}", res.trim_end());
+        Ok(())
+    }
+}
+
+impl CmdExector for MsgpackEncodeOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let data = process_msgpack_encode(&self.input)?;
+        write_codec_output(&data, self.codec, self.output.as_deref())
+    }
+}
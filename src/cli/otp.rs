@@ -0,0 +1,197 @@
+use clap::{Args, Parser, ValueEnum};
+use colored::Colorize;
+use data_encoding::BASE32_NOPAD;
+use enum_dispatch::enum_dispatch;
+
+use crate::{
+    process::otp::{
+        decode_secret, process_otp_generate, process_otp_new_secret, process_otp_verify, OtpParams,
+    },
+    utils::read_secret,
+    CmdExector,
+};
+
+#[derive(Debug, Parser)]
+#[enum_dispatch(CmdExector)]
+pub enum OtpSubCommand {
+    #[command(about = "Create a new base32 secret and its otpauth uri")]
+    New(OtpNewOpts),
+    #[command(about = "Generate a TOTP or HOTP code")]
+    Generate(OtpGenerateOpts),
+    #[command(about = "Verify a TOTP or HOTP code")]
+    Verify(OtpVerifyOpts),
+    #[command(about = "Show the parameters of an otpauth uri")]
+    Parse(OtpParseOpts),
+}
+
+/// Parameters of the one-time password.
+#[derive(Debug, Args)]
+pub struct OtpParamsArgs {
+    /// Base32 secret. If neither `--secret` nor `--uri` is specified,
+    /// the secret or uri is read from stdin.
+    #[arg(long)]
+    pub secret: Option<String>,
+    /// The otpauth uri, which overrides the other parameters.
+    #[arg(long, conflicts_with = "secret")]
+    pub uri: Option<String>,
+    /// Time-based (RFC 6238) or counter-based (RFC 4226) one-time password.
+    #[arg(long = "type", value_enum, default_value_t = OtpType::Totp)]
+    pub kind: OtpType,
+    /// Hash algorithm of the HMAC.
+    #[arg(long, value_enum, default_value_t = OtpAlgorithm::Sha1)]
+    pub algorithm: OtpAlgorithm,
+    /// Number of digits of the code.
+    #[arg(long, default_value_t = 6, value_parser = clap::value_parser!(u8).range(6..=8))]
+    pub digits: u8,
+    /// Time step of TOTP in seconds.
+    #[arg(long, default_value_t = 30, value_parser = clap::value_parser!(u64).range(1..))]
+    pub period: u64,
+    /// Counter of HOTP.
+    #[arg(long, default_value_t = 0)]
+    pub counter: u64,
+}
+
+#[derive(Debug, Parser)]
+pub struct OtpNewOpts {
+    /// Length of the secret in bytes.
+    #[arg(long, default_value_t = 20, value_parser = clap::value_parser!(u16).range(10..=128))]
+    pub bytes: u16,
+    /// Issuer shown in authenticator apps, e.g. the company name.
+    #[arg(long)]
+    pub issuer: Option<String>,
+    /// Account shown in authenticator apps, e.g. an email.
+    #[arg(long)]
+    pub account: Option<String>,
+    #[arg(long = "type", value_enum, default_value_t = OtpType::Totp)]
+    pub kind: OtpType,
+    #[arg(long, value_enum, default_value_t = OtpAlgorithm::Sha1)]
+    pub algorithm: OtpAlgorithm,
+    #[arg(long, default_value_t = 6, value_parser = clap::value_parser!(u8).range(6..=8))]
+    pub digits: u8,
+    #[arg(long, default_value_t = 30, value_parser = clap::value_parser!(u64).range(1..))]
+    pub period: u64,
+    #[arg(long, default_value_t = 0)]
+    pub counter: u64,
+}
+
+#[derive(Debug, Parser)]
+pub struct OtpGenerateOpts {
+    #[command(flatten)]
+    pub params: OtpParamsArgs,
+    /// Unix time in seconds used for TOTP instead of now.
+    #[arg(long)]
+    pub time: Option<u64>,
+}
+
+#[derive(Debug, Parser)]
+pub struct OtpVerifyOpts {
+    #[command(flatten)]
+    pub params: OtpParamsArgs,
+    /// The code to verify.
+    #[arg(long)]
+    pub code: String,
+    /// Unix time in seconds used for TOTP instead of now.
+    #[arg(long)]
+    pub time: Option<u64>,
+    /// Number of time steps (TOTP) or following counters (HOTP) also accepted, at most 10.
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u64).range(0..=10))]
+    pub window: u64,
+}
+
+#[derive(Debug, Parser)]
+pub struct OtpParseOpts {
+    /// The otpauth uri.
+    pub uri: String,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum OtpType {
+    Totp,
+    Hotp,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum OtpAlgorithm {
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+impl OtpParamsArgs {
+    fn params(&self) -> anyhow::Result<OtpParams> {
+        let input = match (&self.uri, &self.secret) {
+            (Some(uri), _) => uri.clone(),
+            (None, Some(secret)) => secret.clone(),
+            (None, None) => read_secret("Secret or otpauth uri: ")?,
+        };
+        if input.starts_with("otpauth://") {
+            return OtpParams::from_uri(&input);
+        }
+        OtpParams::try_new(
+            self.kind,
+            decode_secret(&input)?,
+            self.algorithm,
+            self.digits,
+            self.period,
+            self.counter,
+        )
+    }
+}
+
+impl CmdExector for OtpNewOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let secret = process_otp_new_secret(self.bytes as usize);
+        let mut params = OtpParams::try_new(
+            self.kind,
+            secret,
+            self.algorithm,
+            self.digits,
+            self.period,
+            self.counter,
+        )?;
+        params.issuer = self.issuer;
+        params.account = self.account;
+        println!(
+            "secret: {}",
+            BASE32_NOPAD.encode(&params.secret).bright_cyan()
+        );
+        println!("uri: {}", params.to_uri().purple());
+        Ok(())
+    }
+}
+
+impl CmdExector for OtpGenerateOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let params = self.params.params()?;
+        println!("{}", process_otp_generate(&params, self.time)?);
+        Ok(())
+    }
+}
+
+impl CmdExector for OtpVerifyOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let params = self.params.params()?;
+        match process_otp_verify(&params, &self.code, self.time, self.window)? {
+            Some(offset) => println!("{}", format!("√ Code verified, offset {offset}").green()),
+            None => println!("{}", "x Code not verified".red()),
+        }
+        Ok(())
+    }
+}
+
+impl CmdExector for OtpParseOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let params = OtpParams::from_uri(&self.uri)?;
+        println!("type: {:?}", params.kind);
+        println!("issuer: {}", params.issuer.unwrap_or_default());
+        println!("account: {}", params.account.unwrap_or_default());
+        println!("secret: {}", BASE32_NOPAD.encode(&params.secret));
+        println!("algorithm: {:?}", params.algorithm);
+        println!("digits: {}", params.digits);
+        match params.kind {
+            OtpType::Totp => println!("period: {}", params.period),
+            OtpType::Hotp => println!("counter: {}", params.counter),
+        }
+        Ok(())
+    }
+}
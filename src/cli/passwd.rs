@@ -0,0 +1,107 @@
+use clap::{Parser, ValueEnum};
+use colored::Colorize;
+use enum_dispatch::enum_dispatch;
+
+use crate::{
+    process::{
+        passwd::{
+            process_hash_argon2, process_hash_bcrypt, process_hash_scrypt, process_passwd_verify,
+        },
+        process_genpass,
+    },
+    utils::read_secret,
+    CmdExector,
+};
+
+#[derive(Debug, Parser)]
+#[enum_dispatch(CmdExector)]
+pub enum PasswdSubCommand {
+    #[command(about = "Hash a password read from stdin into a PHC string")]
+    Hash(PasswdHashOpts),
+    #[command(about = "Verify a password read from stdin against a hash")]
+    Verify(PasswdVerifyOpts),
+}
+
+#[derive(Debug, Parser)]
+pub struct PasswdHashOpts {
+    /// Hash algorithm.
+    #[arg(long, value_enum, default_value_t = PasswdAlgorithm::Argon2id)]
+    pub algorithm: PasswdAlgorithm,
+    /// Argon2 memory cost in KiB.
+    #[arg(long, default_value_t = 19 * 1024)]
+    pub memory: u32,
+    /// Argon2 time cost.
+    #[arg(long, default_value_t = 2)]
+    pub iterations: u32,
+    /// Argon2 parallelism.
+    #[arg(long, default_value_t = 1)]
+    pub parallelism: u32,
+    /// bcrypt cost, the work factor is `2^cost`.
+    #[arg(long, default_value_t = 12, value_parser = clap::value_parser!(u32).range(4..=31))]
+    pub cost: u32,
+    /// scrypt CPU/memory cost, the work factor is `2^log-n`.
+    #[arg(long, default_value_t = 17)]
+    pub log_n: u8,
+    /// scrypt block size.
+    #[arg(long, default_value_t = 8)]
+    pub block_size: u32,
+    /// scrypt parallelization.
+    #[arg(long = "scrypt-p", default_value_t = 1)]
+    pub scrypt_p: u32,
+    /// Generate a random password instead of reading it from stdin, and print it.
+    #[arg(long, default_value_t = false)]
+    pub generate: bool,
+    /// Length of the generated password.
+    #[arg(short, long, default_value_t = 16, requires = "generate")]
+    pub length: u8,
+}
+
+#[derive(Debug, Parser)]
+pub struct PasswdVerifyOpts {
+    /// The PHC string or bcrypt hash to verify against.
+    #[arg(long)]
+    pub hash: String,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum PasswdAlgorithm {
+    Argon2id,
+    Bcrypt,
+    Scrypt,
+}
+
+impl CmdExector for PasswdHashOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let password = match self.generate {
+            true => process_genpass(self.length, true, true, true, true)?,
+            false => read_secret("Password: ")?,
+        };
+        let hash = match self.algorithm {
+            PasswdAlgorithm::Argon2id => {
+                process_hash_argon2(&password, self.memory, self.iterations, self.parallelism)?
+            }
+            PasswdAlgorithm::Bcrypt => process_hash_bcrypt(&password, self.cost)?,
+            PasswdAlgorithm::Scrypt => {
+                process_hash_scrypt(&password, self.log_n, self.block_size, self.scrypt_p)?
+            }
+        };
+        if self.generate {
+            println!("password: {}", password.bright_cyan());
+            println!("hash: {}", hash.purple());
+        } else {
+            println!("{hash}");
+        }
+        Ok(())
+    }
+}
+
+impl CmdExector for PasswdVerifyOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let password = read_secret("Password: ")?;
+        match process_passwd_verify(&password, &self.hash)? {
+            true => println!("{}", "√ Password verified".green()),
+            false => println!("{}", "x Password not verified".red()),
+        }
+        Ok(())
+    }
+}
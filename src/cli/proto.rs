@@ -0,0 +1,42 @@
+use clap::Parser;
+use enum_dispatch::enum_dispatch;
+
+use crate::{process::proto::process_proto_decode, CmdExector};
+
+use super::{verify_file, Codec};
+
+#[derive(Debug, Parser)]
+#[enum_dispatch(CmdExector)]
+pub enum ProtoSubCommand {
+    #[command(about = "Show the fields of protobuf bytes, with or without a .proto schema")]
+    Decode(ProtoDecodeOpts),
+}
+
+#[derive(Debug, Parser)]
+pub struct ProtoDecodeOpts {
+    /// Specify the input file. If -, it means input from stdin
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+    /// Codec of the input, e.g. hex or base64. If not specified, the input is raw protobuf
+    #[arg(short, long, value_enum)]
+    pub codec: Option<Codec>,
+    /// A .proto file to name the fields and decode them with their declared types
+    #[arg(short, long, value_parser = verify_file)]
+    pub proto: Option<String>,
+    /// The message of the input, e.g. `pkg.User`. Defaults to the first message of the .proto file
+    #[arg(short, long, requires = "proto")]
+    pub message: Option<String>,
+}
+
+impl CmdExector for ProtoDecodeOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let res = process_proto_decode(
+            &self.input,
+            self.codec,
+            self.proto.as_deref(),
+            self.message.as_deref(),
+        )?;
+        print!("{}", res);
+        Ok(())
+    }
+}
@@ -1,16 +1,373 @@
-use clap::Parser;
+use chrono::{
+    format::{Item, StrftimeItems},
+    DateTime, Duration, FixedOffset,
+};
+use chrono_tz::Tz;
+use clap::{Args, Parser, ValueEnum};
+use colored::Colorize;
+use enum_dispatch::enum_dispatch;
+use std::fmt;
 
-use crate::{process::process_unix_to_string, CmdExector};
+use crate::{
+    process::{
+        process_unix_to_datetime,
+        time::{
+            convert_duration, format_in_zone, format_iso_duration, format_time, format_zones,
+            humanize_duration, parse_duration, process_time_add, process_time_diff,
+            process_time_parse, time_json, to_zone,
+        },
+    },
+    CmdExector,
+};
 
 #[derive(Debug, Parser)]
+#[command(args_conflicts_with_subcommands = true)]
 pub struct TimeOpts {
-    /// Specify the unix to format, if 0, means now.
-    #[arg(default_value_t = 0)]
-    pub unix: u64,
+    #[command(subcommand)]
+    pub cmd: Option<TimeSubCommand>,
+
+    /// Specify the unix to format, if 0, means now. Negative values are before 1970
+    #[arg(default_value_t = 0, allow_negative_numbers = true)]
+    pub unix: i64,
+
+    /// Unit of the timestamp. If not specified, it's guessed from the magnitude:
+    /// seconds below 10^11, then milliseconds, microseconds and nanoseconds
+    #[arg(long, value_enum)]
+    pub unit: Option<TimeUnit>,
+
+    #[command(flatten)]
+    pub zone: TimeZoneArgs,
+
+    #[command(flatten)]
+    pub output: TimeOutputArgs,
+}
+
+#[derive(Debug, Parser)]
+#[enum_dispatch(CmdExector)]
+pub enum TimeSubCommand {
+    #[command(about = "Parse a date into a unix timestamp")]
+    Parse(TimeParseOpts),
+    #[command(about = "Add a duration to a time, e.g. `3d2h`, `-90m` or `P1DT2H`")]
+    Add(TimeAddOpts),
+    #[command(about = "Show the duration between two times")]
+    Diff(TimeDiffOpts),
+    #[command(about = "Convert a duration between units and ISO 8601")]
+    Duration(TimeDurationOpts),
+}
+
+#[derive(Debug, Parser)]
+pub struct TimeParseOpts {
+    /// The date, e.g. `2024-05-01 10:00 +0800`, RFC 3339, RFC 2822, a log timestamp,
+    /// `@1714528800`, or relative like `yesterday 9am`, `last monday` and `1h 30m ago`.
+    /// Dates without an offset are in the local time zone, or the one of `--tz`.
+    pub date: String,
+
+    #[command(flatten)]
+    pub zone: TimeZoneArgs,
+
+    #[command(flatten)]
+    pub output: TimeOutputArgs,
+}
+
+#[derive(Debug, Parser)]
+pub struct TimeAddOpts {
+    /// The time, a unix timestamp in any unit or a date like in `time parse`
+    #[arg(allow_negative_numbers = true)]
+    pub time: String,
+
+    /// The duration to add, like `1w2d`, `3h30m`, `-15m` or ISO 8601 `P1DT2H`.
+    /// A bare number is seconds
+    #[arg(value_parser = parse_duration_arg, allow_hyphen_values = true)]
+    pub duration: Duration,
+
+    #[command(flatten)]
+    pub zone: TimeZoneArgs,
+
+    #[command(flatten)]
+    pub output: TimeOutputArgs,
+}
+
+#[derive(Debug, Parser)]
+pub struct TimeDiffOpts {
+    /// The start, a unix timestamp in any unit or a date like in `time parse`
+    #[arg(allow_negative_numbers = true)]
+    pub from: String,
+
+    /// The end, the duration is negative if it's before the start
+    #[arg(allow_negative_numbers = true)]
+    pub to: String,
+
+    #[command(flatten)]
+    pub zone: TimeZoneArgs,
+}
+
+#[derive(Debug, Parser)]
+pub struct TimeDurationOpts {
+    /// The duration, like `1w2d`, `3h30m`, `-15m` or ISO 8601 `P1DT2H`.
+    /// A bare number is seconds
+    #[arg(value_parser = parse_duration_arg, allow_hyphen_values = true)]
+    pub duration: Duration,
+
+    /// Print only the duration as a number of this unit
+    #[arg(long, value_enum)]
+    pub to: Option<DurationUnit>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum TimeUnit {
+    #[value(name = "s", alias = "seconds")]
+    Seconds,
+    #[value(name = "ms", alias = "millis")]
+    Millis,
+    #[value(name = "us", alias = "micros")]
+    Micros,
+    #[value(name = "ns", alias = "nanos")]
+    Nanos,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum DurationUnit {
+    #[value(name = "ms", alias = "millis")]
+    Millis,
+    #[value(name = "s", alias = "seconds")]
+    Seconds,
+    #[value(name = "m", alias = "minutes")]
+    Minutes,
+    #[value(name = "h", alias = "hours")]
+    Hours,
+    #[value(name = "d", alias = "days")]
+    Days,
+    #[value(name = "w", alias = "weeks")]
+    Weeks,
+}
+
+/// The zones to show a time in.
+#[derive(Debug, Args)]
+pub struct TimeZoneArgs {
+    /// Show the time in this IANA time zone, e.g. `Asia/Shanghai`, instead of the local one
+    #[arg(long, value_parser = parse_tz)]
+    pub tz: Option<Tz>,
+
+    /// Show the time in UTC instead of the local time zone
+    #[arg(long, default_value_t = false, conflicts_with = "tz")]
+    pub utc: bool,
+
+    /// Also show the time in these IANA time zones, separated by commas
+    #[arg(long, env = "RCLI_TIME_ZONES", value_delimiter = ',', value_parser = parse_tz)]
+    pub zones: Vec<Tz>,
+}
+
+/// How to print a time.
+#[derive(Debug, Args)]
+pub struct TimeOutputArgs {
+    /// Print only the time in this format: rfc3339, rfc2822, iso-week, http, unix, excel,
+    /// or a strftime pattern like `%Y-%m-%d %H:%M`
+    #[arg(short, long, value_parser = parse_time_format)]
+    pub format: Option<TimeFormat>,
+
+    /// Print the time in every preset format as JSON
+    #[arg(long, default_value_t = false, conflicts_with = "format")]
+    pub json: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimeFormat {
+    /// `2024-05-01T10:00:00+08:00`
+    Rfc3339,
+    /// `Wed, 01 May 2024 10:00:00 +0800`
+    Rfc2822,
+    /// ISO 8601 week date, `2024-W18-3`
+    IsoWeek,
+    /// `Wed, 01 May 2024 02:00:00 GMT`, always in GMT
+    Http,
+    /// Unix seconds
+    Unix,
+    /// Days since 1899-12-30 in local time, as spreadsheets store dates
+    Excel,
+    /// A strftime pattern
+    Custom(String),
+}
+
+impl TimeZoneArgs {
+    /// The zone of `--tz` or `--utc`, `None` for the local one.
+    pub fn zone(&self) -> Option<Tz> {
+        match self.utc {
+            true => Some(Tz::UTC),
+            false => self.tz,
+        }
+    }
 }
 
 impl CmdExector for TimeOpts {
     async fn execute(self) -> anyhow::Result<()> {
-        process_unix_to_string(self.unix as i64)
+        if let Some(cmd) = self.cmd {
+            return cmd.execute().await;
+        }
+        let (t, unit) = process_unix_to_datetime(self.unix, self.unit)?;
+        let zone = self.zone.zone();
+        if self.output.json {
+            let mut json = time_json(&to_zone(&t, zone), &self.zone.zones);
+            if let Some(unit) = unit {
+                json["unit"] = unit.to_string().into();
+            }
+            println!("{}", serde_json::to_string_pretty(&json)?);
+            return Ok(());
+        }
+        match (&self.output.format, unit) {
+            (Some(format), _) => println!("{}", format_time(&to_zone(&t, zone), format)),
+            (None, Some(unit)) => println!(
+                "{} ({}): {}",
+                self.unix.to_string().yellow(),
+                unit,
+                format_in_zone(&t, zone).purple()
+            ),
+            (None, None) => println!(
+                "{}: {}",
+                t.timestamp().to_string().yellow(),
+                format_in_zone(&t, zone).purple()
+            ),
+        }
+        print!(
+            "{}",
+            format_zones(&t, &self.zone.zones, self.output.format.as_ref())
+        );
+        Ok(())
+    }
+}
+
+impl CmdExector for TimeParseOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let t = process_time_parse(&self.date, self.zone.zone())?;
+        print_time(&t, &self.zone, &self.output)
+    }
+}
+
+impl CmdExector for TimeAddOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let t = process_time_add(&self.time, self.duration, self.zone.zone())?;
+        print_time(&t, &self.zone, &self.output)
+    }
+}
+
+impl CmdExector for TimeDiffOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let d = process_time_diff(&self.from, &self.to, self.zone.zone())?;
+        let seconds = convert_duration(d, DurationUnit::Seconds);
+        println!("seconds: {}", seconds.to_string().yellow());
+        println!("human:   {}", humanize_duration(d).purple());
+        println!("iso8601: {}", format_iso_duration(d));
+        Ok(())
+    }
+}
+
+impl CmdExector for TimeDurationOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let d = self.duration;
+        if let Some(unit) = self.to {
+            println!("{}", convert_duration(d, unit));
+            return Ok(());
+        }
+        println!("seconds: {}", convert_duration(d, DurationUnit::Seconds));
+        println!("minutes: {}", convert_duration(d, DurationUnit::Minutes));
+        println!("hours:   {}", convert_duration(d, DurationUnit::Hours));
+        println!("days:    {}", convert_duration(d, DurationUnit::Days));
+        println!("iso8601: {}", format_iso_duration(d).yellow());
+        println!("human:   {}", humanize_duration(d).purple());
+        Ok(())
+    }
+}
+
+/// Print a parsed time as `--json`, in `--format` or as seconds, millis and RFC 3339,
+/// then in the `--zones`.
+fn print_time(
+    t: &DateTime<FixedOffset>,
+    zone: &TimeZoneArgs,
+    output: &TimeOutputArgs,
+) -> anyhow::Result<()> {
+    if output.json {
+        let json = time_json(t, &zone.zones);
+        println!("{}", serde_json::to_string_pretty(&json)?);
+        return Ok(());
+    }
+    match &output.format {
+        Some(format) => println!("{}", format_time(t, format)),
+        None => {
+            println!("seconds: {}", t.timestamp().to_string().yellow());
+            println!("millis:  {}", t.timestamp_millis().to_string().yellow());
+            println!("rfc3339: {}", t.to_rfc3339().purple());
+        }
+    }
+    let format = output.format.as_ref();
+    print!("{}", format_zones(&t.to_utc(), &zone.zones, format));
+    Ok(())
+}
+
+impl fmt::Display for TimeUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unit = match self {
+            TimeUnit::Seconds => "seconds",
+            TimeUnit::Millis => "milliseconds",
+            TimeUnit::Micros => "microseconds",
+            TimeUnit::Nanos => "nanoseconds",
+        };
+        write!(f, "{unit}")
+    }
+}
+
+fn parse_time_format(format: &str) -> Result<TimeFormat, String> {
+    let res = match format {
+        "rfc3339" => TimeFormat::Rfc3339,
+        "rfc2822" => TimeFormat::Rfc2822,
+        "iso-week" => TimeFormat::IsoWeek,
+        "http" => TimeFormat::Http,
+        "unix" => TimeFormat::Unix,
+        "excel" => TimeFormat::Excel,
+        pattern if pattern.contains('%') => {
+            if StrftimeItems::new(pattern).any(|item| item == Item::Error) {
+                return Err(format!("invalid strftime pattern `{pattern}`"));
+            }
+            TimeFormat::Custom(pattern.to_string())
+        }
+        _ => {
+            return Err(
+                "expected rfc3339, rfc2822, iso-week, http, unix, excel or a strftime pattern"
+                    .to_string(),
+            )
+        }
+    };
+    Ok(res)
+}
+
+fn parse_duration_arg(duration: &str) -> Result<Duration, String> {
+    parse_duration(duration).map_err(|e| e.to_string())
+}
+
+fn parse_tz(name: &str) -> Result<Tz, String> {
+    name.trim().parse().map_err(|_| {
+        format!("unknown time zone `{name}`, expected an IANA name like Asia/Shanghai")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_time_format() {
+        assert_eq!(parse_time_format("iso-week"), Ok(TimeFormat::IsoWeek));
+        assert_eq!(
+            parse_time_format("%Y-%m-%d"),
+            Ok(TimeFormat::Custom("%Y-%m-%d".into()))
+        );
+        assert!(parse_time_format("%Q").is_err());
+        assert!(parse_time_format("iso").is_err());
+    }
+
+    #[test]
+    fn test_time_duration_args() {
+        let opts = TimeDurationOpts::try_parse_from(["duration", "-1h30m", "--to", "m"]).unwrap();
+        assert_eq!(opts.duration, Duration::minutes(-90));
+        assert_eq!(opts.to, Some(DurationUnit::Minutes));
+        assert!(TimeDurationOpts::try_parse_from(["duration", "P1Y"]).is_err());
     }
 }
@@ -10,3 +10,9 @@ use enum_dispatch::enum_dispatch;
 pub trait CmdExector {
     async fn execute(self) -> anyhow::Result<()>;
 }
+
+impl<T: CmdExector> CmdExector for Box<T> {
+    async fn execute(self) -> anyhow::Result<()> {
+        (*self).execute().await
+    }
+}
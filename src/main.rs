@@ -1,10 +1,14 @@
 use clap::Parser;
-use rcli::{cli, CmdExector};
+use rcli::{cli, utils, CmdExector};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     tracing_subscriber::fmt::init();
     let opts = cli::Opts::parse();
+    if let Some(seed) = opts.seed {
+        eprintln!("warning: --seed is set, all random output is predictable and insecure");
+        utils::set_insecure_seed(seed);
+    }
     opts.cmd.execute().await?;
     Ok(())
 }
@@ -1,34 +1,282 @@
-use anyhow::Ok;
-use base64::engine::GeneralPurpose;
+use std::{
+    io::{self, Read, Write},
+    path::Path,
+};
+
+use anyhow::anyhow;
+use base64::alphabet;
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
 use base64::prelude::*;
-use base64::Engine;
+use base64::read::DecoderReader;
+use base64::write::EncoderWriter;
+use percent_encoding::percent_decode_str;
+
+use crate::cli::base64::{Base64DecodeFormat, Base64Format};
 
-use super::get_input;
-use crate::cli::base64::Base64Format;
+/// PEM base64 lines are 64 characters long.
+const PEM_LINE_LENGTH: usize = 64;
 
+/// Stream the input into base64, optionally wrapping lines at `wrap` characters.
+/// The output always ends with a newline.
 pub fn process_encode(
-    input: &str,
+    mut reader: impl Read,
+    writer: impl Write,
     format: Base64Format,
     no_padding: bool,
-) -> anyhow::Result<String> {
-    let buf = get_input(input)?;
+    wrap: Option<usize>,
+) -> anyhow::Result<()> {
     let engine = get_decode_engine(format, no_padding);
-    let res = engine.encode(buf);
-    Ok(res)
+    let mut writer = LineWrapper::new(writer, wrap.unwrap_or(0));
+    let mut encoder = EncoderWriter::new(&mut writer, &engine);
+    io::copy(&mut reader, &mut encoder)?;
+    let writer = encoder.finish()?;
+    writer.inner.write_all(b"\n")?;
+    writer.inner.flush()?;
+    Ok(())
 }
 
+/// The alphabet and padding of a base64 input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Base64Variant {
+    pub format: Base64Format,
+    pub padding: bool,
+}
+
+/// Stream the base64 input into the decoded bytes, whitespace and newlines are ignored.
+/// With [`Base64DecodeFormat::Auto`], the alphabet and padding are detected while
+/// decoding and returned.
 pub fn process_decode(
-    input: &str,
-    format: Base64Format,
+    reader: impl Read,
+    mut writer: impl Write,
+    format: Base64DecodeFormat,
     no_padding: bool,
-) -> anyhow::Result<Vec<u8>> {
-    let buf = get_input(input)?;
+) -> anyhow::Result<Base64Variant> {
+    let format = match format {
+        Base64DecodeFormat::Auto => return decode_auto(reader, writer),
+        Base64DecodeFormat::Standard => Base64Format::Standard,
+        Base64DecodeFormat::Urlsafe => Base64Format::Urlsafe,
+    };
     let engine = get_decode_engine(format, no_padding);
-    let res = engine.decode(buf)?;
-    Ok(res)
+    let mut decoder = DecoderReader::new(SkipWhitespace { inner: reader }, &engine);
+    io::copy(&mut decoder, &mut writer)?;
+    writer.flush()?;
+    Ok(Base64Variant {
+        format,
+        padding: !no_padding,
+    })
+}
+
+/// URL-safe characters are translated to standard ones, so a single engine that
+/// accepts both padded and unpadded input decodes every variant. The alphabets may
+/// only be found mixed in a later chunk, so nothing is written before the whole
+/// input is decoded.
+fn decode_auto(reader: impl Read, mut writer: impl Write) -> anyhow::Result<Base64Variant> {
+    let config =
+        GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent);
+    let engine = GeneralPurpose::new(&alphabet::STANDARD, config);
+    let mut detector = DetectVariant {
+        inner: SkipWhitespace { inner: reader },
+        standard: false,
+        urlsafe: false,
+        padding: false,
+    };
+    let mut decoder = DecoderReader::new(&mut detector, &engine);
+    let mut decoded = Vec::new();
+    decoder.read_to_end(&mut decoded)?;
+    writer.write_all(&decoded)?;
+    writer.flush()?;
+    Ok(Base64Variant {
+        format: match detector.urlsafe {
+            true => Base64Format::Urlsafe,
+            false => Base64Format::Standard,
+        },
+        padding: detector.padding,
+    })
+}
+
+/// Encode the bytes into a `data:<mime>;base64,...` URI. If `mime` is not given,
+/// it's sniffed from the magic bytes, then from the extension of `filename`.
+pub fn process_datauri_encode(data: &[u8], mime: Option<&str>, filename: Option<&str>) -> String {
+    let mime = match mime {
+        Some(mime) => mime,
+        None => sniff_mime(data, filename),
+    };
+    let engine = get_decode_engine(Base64Format::Standard, false);
+    format!("data:{mime};base64,{}", engine.encode(data))
+}
+
+/// Parse a data URI into its MIME type and bytes, both base64 and percent-encoded
+/// data are supported.
+pub fn process_datauri_decode(uri: &str) -> anyhow::Result<(String, Vec<u8>)> {
+    let uri = uri.trim();
+    let rest = uri
+        .strip_prefix("data:")
+        .ok_or_else(|| anyhow!("invalid data uri, it must start with `data:`"))?;
+    let (meta, data) = rest
+        .split_once(',')
+        .ok_or_else(|| anyhow!("invalid data uri, missing `,` before the data"))?;
+    let (mime, is_base64) = match meta.strip_suffix(";base64") {
+        Some(mime) => (mime, true),
+        None => (meta, false),
+    };
+    let mime = match mime {
+        "" => "text/plain;charset=US-ASCII".to_string(),
+        mime => mime.to_string(),
+    };
+    let data = match is_base64 {
+        true => {
+            let mut res = Vec::new();
+            process_decode(data.as_bytes(), &mut res, Base64DecodeFormat::Auto, false)?;
+            res
+        }
+        false => percent_decode_str(data).collect(),
+    };
+    Ok((mime, data))
+}
+
+/// Wrap the bytes in PEM armor: `-----BEGIN <label>-----`, base64 lines of
+/// 64 characters and `-----END <label>-----`.
+pub fn process_pem_encode(data: &[u8], label: &str) -> anyhow::Result<String> {
+    check_pem_label(label)?;
+    let mut body = Vec::new();
+    process_encode(
+        data,
+        &mut body,
+        Base64Format::Standard,
+        false,
+        Some(PEM_LINE_LENGTH),
+    )?;
+    Ok(format!(
+        "-----BEGIN {label}-----\n{}-----END {label}-----\n",
+        String::from_utf8(body)?
+    ))
+}
+
+/// Unwrap the first PEM block, or the first one with the given label,
+/// and return its label and bytes.
+pub fn process_pem_decode(text: &str, label: Option<&str>) -> anyhow::Result<(String, Vec<u8>)> {
+    let mut rest = text;
+    while let Some(start) = rest.find("-----BEGIN ") {
+        let block = &rest[start + "-----BEGIN ".len()..];
+        let (found, block) = block
+            .split_once("-----")
+            .ok_or_else(|| anyhow!("invalid pem, unterminated BEGIN line"))?;
+        let end = format!("-----END {found}-----");
+        let body_len = block
+            .find(&end)
+            .ok_or_else(|| anyhow!("invalid pem, missing `{end}`"))?;
+        rest = &block[body_len + end.len()..];
+        if label.is_some_and(|label| label != found) {
+            continue;
+        }
+        let mut data = Vec::new();
+        process_decode(
+            &block.as_bytes()[..body_len],
+            &mut data,
+            Base64DecodeFormat::Standard,
+            false,
+        )?;
+        return Ok((found.to_string(), data));
+    }
+    match label {
+        Some(label) => Err(anyhow!("no pem block with label `{label}` found")),
+        None => Err(anyhow!("no pem block found")),
+    }
+}
+
+/// Guess the MIME type from the magic bytes, then from the extension of `filename`.
+pub fn sniff_mime(data: &[u8], filename: Option<&str>) -> &'static str {
+    const MAGIC: &[(&[u8], &str)] = &[
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"\x00\x00\x01\x00", "image/x-icon"),
+        (b"%PDF-", "application/pdf"),
+        (b"PK\x03\x04", "application/zip"),
+        (b"\x1f\x8b", "application/gzip"),
+        (b"\x00asm", "application/wasm"),
+        (b"wOFF", "font/woff"),
+        (b"wOF2", "font/woff2"),
+        (b"ID3", "audio/mpeg"),
+        (b"OggS", "audio/ogg"),
+    ];
+    if let Some((_, mime)) = MAGIC.iter().find(|(magic, _)| data.starts_with(magic)) {
+        return mime;
+    }
+    if data.starts_with(b"RIFF") && data.get(8..12) == Some(b"WEBP") {
+        return "image/webp";
+    }
+    if let Some(mime) = sniff_ftyp(data).or_else(|| sniff_bmp(data)) {
+        return mime;
+    }
+
+    let ext = filename
+        .and_then(|name| Path::new(name).extension())
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase);
+    let mime = match ext.as_deref() {
+        Some("svg") => Some("image/svg+xml"),
+        Some("html" | "htm") => Some("text/html"),
+        Some("css") => Some("text/css"),
+        Some("js" | "mjs") => Some("text/javascript"),
+        Some("json") => Some("application/json"),
+        Some("xml") => Some("application/xml"),
+        Some("csv") => Some("text/csv"),
+        Some("txt") => Some("text/plain"),
+        _ => None,
+    };
+    match mime {
+        Some(mime) => mime,
+        None if std::str::from_utf8(data).is_ok() => "text/plain",
+        None => "application/octet-stream",
+    }
 }
 
-fn get_decode_engine(format: Base64Format, no_padding: bool) -> GeneralPurpose {
+/// An ISO base media file, identified by the major brand of its `ftyp` box.
+fn sniff_ftyp(data: &[u8]) -> Option<&'static str> {
+    if data.get(4..8) != Some(b"ftyp") {
+        return None;
+    }
+    let mime = match data.get(8..12)? {
+        b"heic" | b"heix" | b"heim" | b"heis" | b"hevc" | b"hevx" => "image/heic",
+        b"mif1" | b"msf1" => "image/heif",
+        b"avif" | b"avis" => "image/avif",
+        b"qt  " => "video/quicktime",
+        b"M4A " | b"M4B " => "audio/mp4",
+        b"3gp4" | b"3gp5" | b"3gp6" | b"3g2a" => "video/3gpp",
+        b"isom" | b"iso2" | b"iso4" | b"iso5" | b"iso6" | b"mp41" | b"mp42" | b"avc1" | b"dash"
+        | b"M4V " => "video/mp4",
+        _ => return None,
+    };
+    Some(mime)
+}
+
+/// A BMP starts with `BM`, but so does text. Check the size of the DIB header too.
+fn sniff_bmp(data: &[u8]) -> Option<&'static str> {
+    if !data.starts_with(b"BM") {
+        return None;
+    }
+    let dib_size = u32::from_le_bytes(data.get(14..18)?.try_into().ok()?);
+    match dib_size {
+        12 | 40 | 52 | 56 | 64 | 108 | 124 => Some("image/bmp"),
+        _ => None,
+    }
+}
+
+/// RFC 7468 labels are printable ASCII, without `-` at either end.
+fn check_pem_label(label: &str) -> anyhow::Result<()> {
+    if label.is_empty()
+        || label.starts_with('-')
+        || label.ends_with('-')
+        || label.bytes().any(|c| !(c.is_ascii_graphic() || c == b' '))
+    {
+        return Err(anyhow!("invalid pem label `{label}`"));
+    }
+    Ok(())
+}
+
+pub fn get_decode_engine(format: Base64Format, no_padding: bool) -> GeneralPurpose {
     match format {
         Base64Format::Standard => match no_padding {
             true => BASE64_STANDARD_NO_PAD,
@@ -41,21 +289,231 @@ fn get_decode_engine(format: Base64Format, no_padding: bool) -> GeneralPurpose {
     }
 }
 
+/// Insert a newline every `width` bytes, `0` disables wrapping.
+pub(crate) struct LineWrapper<W> {
+    inner: W,
+    width: usize,
+    column: usize,
+}
+
+impl<W: Write> LineWrapper<W> {
+    pub(crate) fn new(inner: W, width: usize) -> Self {
+        Self {
+            inner,
+            width,
+            column: 0,
+        }
+    }
+}
+
+impl<W: Write> Write for LineWrapper<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.width == 0 {
+            return self.inner.write(buf);
+        }
+        let mut rest = buf;
+        while !rest.is_empty() {
+            if self.column == self.width {
+                self.inner.write_all(b"\n")?;
+                self.column = 0;
+            }
+            let n = rest.len().min(self.width - self.column);
+            self.inner.write_all(&rest[..n])?;
+            self.column += n;
+            rest = &rest[n..];
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Drop ASCII whitespace, so wrapped or indented base64 can be decoded.
+struct SkipWhitespace<R> {
+    inner: R,
+}
+
+impl<R: Read> Read for SkipWhitespace<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let n = self.inner.read(buf)?;
+            if n == 0 {
+                return Ok(0);
+            }
+            let mut len = 0;
+            for i in 0..n {
+                if !buf[i].is_ascii_whitespace() {
+                    buf[len] = buf[i];
+                    len += 1;
+                }
+            }
+            if len > 0 {
+                return Ok(len);
+            }
+        }
+    }
+}
+
+/// Record the alphabet and padding of the base64 read through it,
+/// translating URL-safe characters to standard ones. Reading fails as soon as
+/// both alphabets are seen, before the chunk is decoded.
+struct DetectVariant<R> {
+    inner: R,
+    standard: bool,
+    urlsafe: bool,
+    padding: bool,
+}
+
+impl<R: Read> Read for DetectVariant<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        for c in buf[..n].iter_mut() {
+            match c {
+                b'+' | b'/' => self.standard = true,
+                b'-' => (*c, self.urlsafe) = (b'+', true),
+                b'_' => (*c, self.urlsafe) = (b'/', true),
+                b'=' => self.padding = true,
+                _ => {}
+            }
+        }
+        if self.standard && self.urlsafe {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "invalid base64, it mixes standard `+/` and url-safe `-_` characters",
+            ));
+        }
+        Ok(n)
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::fs::File;
+
     use super::*;
 
     #[test]
-    fn test_process_encode() {
-        let input = "Cargo.toml";
+    fn test_process_encode() -> anyhow::Result<()> {
         let format = Base64Format::Standard;
-        assert!(process_encode(input, format, false).is_ok());
+        let mut res = Vec::new();
+        process_encode(File::open("Cargo.toml")?, &mut res, format, false, None)?;
+        assert_eq!(res.iter().filter(|c| **c == b'\n').count(), 1);
+
+        let mut res = Vec::new();
+        process_encode(&[0u8; 100][..], &mut res, format, false, Some(76))?;
+        let res = String::from_utf8(res)?;
+        let lines: Vec<&str> = res.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].len(), 76);
+        assert_eq!(lines[1].len(), 136 - 76);
+        assert!(lines[1].ends_with("AA=="));
+        Ok(())
     }
 
     #[test]
-    fn test_process_decode() {
-        let input = "fixtures/b64.txt";
-        let format = Base64Format::Urlsafe;
-        process_decode(input, format, true).unwrap();
+    fn test_process_decode() -> anyhow::Result<()> {
+        let format = Base64DecodeFormat::Urlsafe;
+        let mut res = Vec::new();
+        process_decode(File::open("fixtures/b64.txt")?, &mut res, format, true)?;
+        assert!(res.starts_with(b"[package]"));
+
+        let mut res = Vec::new();
+        let wrapped = "aGVs\n bG8g\r\nd29y bGQ=\n";
+        process_decode(
+            wrapped.as_bytes(),
+            &mut res,
+            Base64DecodeFormat::Standard,
+            false,
+        )?;
+        assert_eq!(res, b"hello world");
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_decode_auto() -> anyhow::Result<()> {
+        let cases = [
+            ("-_8", Base64Format::Urlsafe, false),
+            ("-_8=\n", Base64Format::Urlsafe, true),
+            ("+/8=", Base64Format::Standard, true),
+            ("  +/8 ", Base64Format::Standard, false),
+        ];
+        for (input, format, padding) in cases {
+            let mut res = Vec::new();
+            let variant =
+                process_decode(input.as_bytes(), &mut res, Base64DecodeFormat::Auto, false)?;
+            assert_eq!(res, [0xfb, 0xff], "{input}");
+            assert_eq!(variant, Base64Variant { format, padding }, "{input}");
+        }
+
+        let mut res = Vec::new();
+        assert!(process_decode(&b"+_8="[..], &mut res, Base64DecodeFormat::Auto, false).is_err());
+        assert!(res.is_empty());
+
+        // the url-safe characters are only read after many chunks of standard ones
+        let input = "+/8A".repeat(4000) + "-_8A";
+        let mut res = Vec::new();
+        assert!(
+            process_decode(input.as_bytes(), &mut res, Base64DecodeFormat::Auto, false).is_err()
+        );
+        assert!(res.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sniff_mime() {
+        assert_eq!(sniff_mime(b"\x89PNG\r\n\x1a\n....", None), "image/png");
+        assert_eq!(sniff_mime(b"RIFF\0\0\0\0WEBPVP8 ", None), "image/webp");
+        assert_eq!(
+            sniff_mime(b"<svg></svg>", Some("logo.SVG")),
+            "image/svg+xml"
+        );
+        assert_eq!(sniff_mime(b"hello", Some("-")), "text/plain");
+        assert_eq!(sniff_mime(b"\xfe\xff", None), "application/octet-stream");
+
+        let bmp = b"BM\x46\0\0\0\0\0\0\0\x36\0\0\0\x28\0\0\0";
+        assert_eq!(sniff_mime(bmp, None), "image/bmp");
+        assert_eq!(sniff_mime(b"BMW is a car maker", None), "text/plain");
+        assert_eq!(sniff_mime(b"\0\0\0\x18ftypmp42", None), "video/mp4");
+        assert_eq!(sniff_mime(b"\0\0\0\x18ftypheic", None), "image/heic");
+        assert_eq!(sniff_mime(b"\0\0\0\x1cftypavif", None), "image/avif");
+        assert_eq!(sniff_mime(b"\0\0\0\x14ftypqt  ", None), "video/quicktime");
+    }
+
+    #[test]
+    fn test_datauri() -> anyhow::Result<()> {
+        let uri = process_datauri_encode(b"{}", None, Some("a.json"));
+        assert_eq!(uri, "data:application/json;base64,e30=");
+        let (mime, data) = process_datauri_decode(&uri)?;
+        assert_eq!(
+            (mime.as_str(), data.as_slice()),
+            ("application/json", &b"{}"[..])
+        );
+
+        let (mime, data) = process_datauri_decode("data:,Hello%2C%20World")?;
+        assert_eq!(mime, "text/plain;charset=US-ASCII");
+        assert_eq!(data, b"Hello, World");
+        assert!(process_datauri_decode("http://example.com").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_pem() -> anyhow::Result<()> {
+        let data = [7u8; 50];
+        let pem = process_pem_encode(&data, "RCLI KEY")?;
+        assert!(pem.starts_with("-----BEGIN RCLI KEY-----\n"));
+        assert!(pem.ends_with("\n-----END RCLI KEY-----\n"));
+        assert_eq!(pem.lines().nth(1).map(str::len), Some(64));
+
+        let text = format!("{}\n{pem}", process_pem_encode(b"other", "OTHER")?);
+        assert_eq!(
+            process_pem_decode(&text, None)?,
+            ("OTHER".to_string(), b"other".to_vec())
+        );
+        assert_eq!(process_pem_decode(&text, Some("RCLI KEY"))?.1, data);
+        assert!(process_pem_decode(&text, Some("MISSING")).is_err());
+        assert!(process_pem_encode(&data, "-BAD").is_err());
+        Ok(())
     }
 }
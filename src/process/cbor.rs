@@ -0,0 +1,144 @@
+use anyhow::anyhow;
+use base64::prelude::*;
+use ciborium::Value;
+use serde_json::{Map, Number, Value as JsonValue};
+
+use super::{codec::get_codec_input, csv_convert::serialize_value, get_input};
+use crate::cli::{Codec, OutputFormat};
+
+/// Decode a CBOR item into pretty JSON or YAML, see [`cbor_to_json`] for the mapping.
+pub fn process_cbor_decode(
+    input: &str,
+    codec: Option<Codec>,
+    format: OutputFormat,
+) -> anyhow::Result<String> {
+    let value = read_cbor(&get_codec_input(input, codec)?)?;
+    serialize_value(&cbor_to_json(&value), format)
+}
+
+/// Show a CBOR item in the diagnostic notation of RFC 8949, which keeps tags and byte strings.
+pub fn process_cbor_diag(input: &str, codec: Option<Codec>) -> anyhow::Result<String> {
+    let value = read_cbor(&get_codec_input(input, codec)?)?;
+    Ok(cbor_diag(&value))
+}
+
+/// Encode a JSON document as CBOR.
+pub fn process_cbor_encode(input: &str) -> anyhow::Result<Vec<u8>> {
+    let value: JsonValue = serde_json::from_slice(&get_input(input)?)?;
+    let mut buf = Vec::new();
+    ciborium::into_writer(&value, &mut buf)?;
+    Ok(buf)
+}
+
+/// Read exactly one CBOR item.
+pub fn read_cbor(data: &[u8]) -> anyhow::Result<Value> {
+    let mut rest = data;
+    let value: Value =
+        ciborium::from_reader(&mut rest).map_err(|e| anyhow!("invalid CBOR, {}", e))?;
+    if !rest.is_empty() {
+        return Err(anyhow!("{} trailing bytes after the CBOR item", rest.len()));
+    }
+    Ok(value)
+}
+
+/// Convert CBOR to JSON as in RFC 8949 section 6.1: byte strings become unpadded base64url,
+/// non-finite floats become null and tags are dropped. Map keys that aren't text are written
+/// in diagnostic notation.
+pub fn cbor_to_json(value: &Value) -> JsonValue {
+    match value {
+        Value::Integer(i) => {
+            let i = i128::from(*i);
+            match (u64::try_from(i), i64::try_from(i)) {
+                (Ok(u), _) => JsonValue::from(u),
+                (_, Ok(i)) => JsonValue::from(i),
+                _ => JsonValue::String(i.to_string()),
+            }
+        }
+        Value::Bytes(bytes) => JsonValue::String(BASE64_URL_SAFE_NO_PAD.encode(bytes)),
+        Value::Float(f) => Number::from_f64(*f).map_or(JsonValue::Null, JsonValue::Number),
+        Value::Text(s) => JsonValue::String(s.clone()),
+        Value::Bool(b) => JsonValue::Bool(*b),
+        Value::Null => JsonValue::Null,
+        Value::Tag(_, inner) => cbor_to_json(inner),
+        Value::Array(items) => items.iter().map(cbor_to_json).collect(),
+        Value::Map(entries) => {
+            let map: Map<String, JsonValue> = entries
+                .iter()
+                .map(|(k, v)| {
+                    let key = match k {
+                        Value::Text(s) => s.clone(),
+                        k => cbor_diag(k),
+                    };
+                    (key, cbor_to_json(v))
+                })
+                .collect();
+            JsonValue::Object(map)
+        }
+        _ => JsonValue::Null,
+    }
+}
+
+/// Render CBOR in diagnostic notation, e.g. `{"a": h'0102', "t": 1(1700000000)}`.
+pub fn cbor_diag(value: &Value) -> String {
+    match value {
+        Value::Integer(i) => i128::from(*i).to_string(),
+        Value::Bytes(bytes) => format!("h'{}'", data_encoding::HEXLOWER.encode(bytes)),
+        Value::Float(f) if f.is_nan() => "NaN".to_string(),
+        Value::Float(f) if f.is_infinite() => match f.is_sign_positive() {
+            true => "Infinity".to_string(),
+            false => "-Infinity".to_string(),
+        },
+        Value::Float(f) => format!("{f:?}"),
+        Value::Text(s) => JsonValue::String(s.clone()).to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Null => "null".to_string(),
+        Value::Tag(tag, inner) => format!("{}({})", tag, cbor_diag(inner)),
+        Value::Array(items) => {
+            let items: Vec<_> = items.iter().map(cbor_diag).collect();
+            format!("[{}]", items.join(", "))
+        }
+        Value::Map(entries) => {
+            let entries: Vec<_> = entries
+                .iter()
+                .map(|(k, v)| format!("{}: {}", cbor_diag(k), cbor_diag(v)))
+                .collect();
+            format!("{{{}}}", entries.join(", "))
+        }
+        _ => "undefined".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // {"a": 1, "b": [true, null, -2.5], 1: h'0102', "t": 1(1700000000)}
+    const CBOR: &str = "a4 6161 01 6162 83 f5 f6 f9c100 01 420102 6174 c1 1a6553f100";
+
+    fn sample() -> Vec<u8> {
+        data_encoding::HEXLOWER
+            .decode(CBOR.replace(' ', "").as_bytes())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_cbor_to_json() -> anyhow::Result<()> {
+        let value = read_cbor(&sample())?;
+        assert_eq!(
+            cbor_to_json(&value),
+            serde_json::json!({"a": 1, "b": [true, null, -2.5], "1": "AQI", "t": 1700000000})
+        );
+        assert!(read_cbor(&[0x01, 0x02]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_cbor_diag() -> anyhow::Result<()> {
+        let value = read_cbor(&sample())?;
+        assert_eq!(
+            cbor_diag(&value),
+            r#"{"a": 1, "b": [true, null, -2.5], 1: h'0102', "t": 1(1700000000)}"#
+        );
+        Ok(())
+    }
+}
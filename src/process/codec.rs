@@ -0,0 +1,289 @@
+use std::{
+    io::{Read, Write},
+    sync::LazyLock,
+};
+
+use anyhow::anyhow;
+use base64::Engine;
+use data_encoding::{Encoding, Specification, BASE32, HEXLOWER, HEXLOWER_PERMISSIVE, HEXUPPER};
+
+use super::{
+    b64::{get_decode_engine, process_decode, process_encode, LineWrapper},
+    get_content, get_input,
+};
+use crate::cli::{Base64DecodeFormat, Base64Format, Codec};
+
+const BASE58: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+pub const BASE62: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+const ASCII85: &[u8] =
+    b"!\"#$%&'()*+,-./0123456789:;<=>?@ABCDEFGHIJKLMNOPQRSTUVWXYZ[\\]^_`abcdefghijklmnopqrstu";
+const Z85: &[u8] =
+    b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ.-:+=^!/*?&<>()[]{}@%$#";
+
+/// Crockford's base32, decoding is case-insensitive, maps `I`, `L` to `1` and `O` to `0`,
+/// and ignores `-`.
+static BASE32_CROCKFORD: LazyLock<Encoding> = LazyLock::new(|| {
+    let mut spec = Specification::new();
+    spec.symbols.push_str("0123456789ABCDEFGHJKMNPQRSTVWXYZ");
+    spec.translate.from.push_str("abcdefghjkmnpqrstvwxyzIiLlOo");
+    spec.translate.to.push_str("ABCDEFGHJKMNPQRSTVWXYZ111100");
+    spec.ignore.push('-');
+    spec.encoding()
+        .expect("Crockford base32 specification is valid")
+});
+
+/// Encode the input into text with the codec, optionally wrapping lines at `wrap`
+/// characters. Base64 is streamed, the other codecs read the whole input first.
+/// The output always ends with a newline.
+pub fn process_codec_encode(
+    reader: impl Read,
+    mut writer: impl Write,
+    codec: Codec,
+    wrap: Option<usize>,
+) -> anyhow::Result<()> {
+    if let Some((format, no_padding)) = base64_variant(codec) {
+        return process_encode(reader, writer, format, no_padding, wrap);
+    }
+    let text = codec_encode(&get_content(reader)?, codec)?;
+    let mut wrapper = LineWrapper::new(&mut writer, wrap.unwrap_or(0));
+    wrapper.write_all(text.as_bytes())?;
+    writer.write_all(b"\n")?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Decode the text encoded with the codec into bytes, whitespace and newlines are
+/// ignored. Base64 is streamed, the other codecs read the whole input first.
+pub fn process_codec_decode(
+    reader: impl Read,
+    mut writer: impl Write,
+    codec: Codec,
+) -> anyhow::Result<()> {
+    if let Some((format, no_padding)) = base64_variant(codec) {
+        let format = match format {
+            Base64Format::Standard => Base64DecodeFormat::Standard,
+            Base64Format::Urlsafe => Base64DecodeFormat::Urlsafe,
+        };
+        process_decode(reader, writer, format, no_padding)?;
+        return Ok(());
+    }
+    let mut text = get_content(reader)?;
+    text.retain(|c| !c.is_ascii_whitespace());
+    writer.write_all(&codec_decode(std::str::from_utf8(&text)?, codec)?)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Read the input as is, or decode it with the codec if one is given.
+pub fn get_codec_input(input: &str, codec: Option<Codec>) -> anyhow::Result<Vec<u8>> {
+    let buf = get_input(input)?;
+    match codec {
+        Some(codec) => codec_decode(std::str::from_utf8(&buf)?, codec),
+        None => Ok(buf),
+    }
+}
+
+/// The base64 codec of the alphabet and padding.
+pub fn base64_codec(format: Base64Format, no_padding: bool) -> Codec {
+    match (format, no_padding) {
+        (Base64Format::Standard, false) => Codec::Base64,
+        (Base64Format::Standard, true) => Codec::Base64NoPad,
+        (Base64Format::Urlsafe, false) => Codec::Base64UrlPad,
+        (Base64Format::Urlsafe, true) => Codec::Base64Url,
+    }
+}
+
+/// The alphabet and whether padding is omitted, if the codec is a base64 one.
+fn base64_variant(codec: Codec) -> Option<(Base64Format, bool)> {
+    match codec {
+        Codec::Base64 => Some((Base64Format::Standard, false)),
+        Codec::Base64NoPad => Some((Base64Format::Standard, true)),
+        Codec::Base64Url => Some((Base64Format::Urlsafe, true)),
+        Codec::Base64UrlPad => Some((Base64Format::Urlsafe, false)),
+        _ => None,
+    }
+}
+
+/// Encode the bytes into text with the codec.
+pub fn codec_encode(data: &[u8], codec: Codec) -> anyhow::Result<String> {
+    let res = match codec {
+        Codec::Hex => HEXLOWER.encode(data),
+        Codec::HexUpper => HEXUPPER.encode(data),
+        Codec::Base32 => BASE32.encode(data),
+        Codec::Base32Crockford => BASE32_CROCKFORD.encode(data),
+        Codec::Base58 => base_x::encode(BASE58, data),
+        Codec::Base62 => base_x::encode(BASE62, data),
+        Codec::Base64 | Codec::Base64NoPad | Codec::Base64Url | Codec::Base64UrlPad => {
+            let (format, no_padding) = base64_variant(codec).expect("codec is base64");
+            get_decode_engine(format, no_padding).encode(data)
+        }
+        Codec::Ascii85 => encode_85(data, ASCII85, true),
+        Codec::Z85 => {
+            if !data.len().is_multiple_of(4) {
+                return Err(anyhow!("z85 input length must be a multiple of 4"));
+            }
+            encode_85(data, Z85, false)
+        }
+    };
+    Ok(res)
+}
+
+/// Decode the text encoded with the codec, surrounding whitespace is ignored.
+pub fn codec_decode(text: &str, codec: Codec) -> anyhow::Result<Vec<u8>> {
+    let text = text.trim();
+    let res = match codec {
+        Codec::Hex | Codec::HexUpper => HEXLOWER_PERMISSIVE.decode(text.as_bytes())?,
+        Codec::Base32 => BASE32.decode(text.as_bytes())?,
+        Codec::Base32Crockford => BASE32_CROCKFORD.decode(text.as_bytes())?,
+        Codec::Base58 => base_x::decode(BASE58, text)?,
+        Codec::Base62 => base_x::decode(BASE62, text)?,
+        Codec::Base64 | Codec::Base64NoPad | Codec::Base64Url | Codec::Base64UrlPad => {
+            let (format, no_padding) = base64_variant(codec).expect("codec is base64");
+            get_decode_engine(format, no_padding).decode(text)?
+        }
+        Codec::Ascii85 => {
+            let text = text.strip_prefix("<~").unwrap_or(text);
+            let text = text.strip_suffix("~>").unwrap_or(text);
+            decode_85(text, ASCII85, true)?
+        }
+        Codec::Z85 => {
+            if !text.len().is_multiple_of(5) {
+                return Err(anyhow!("z85 input length must be a multiple of 5"));
+            }
+            decode_85(text, Z85, false)?
+        }
+    };
+    Ok(res)
+}
+
+/// Encode every 4 bytes into 5 base85 digits. A partial last group is padded
+/// with zeros and truncated (Ascii85 only). `zero` abbreviates `\0\0\0\0` to `z`.
+fn encode_85(data: &[u8], alphabet: &[u8], zero: bool) -> String {
+    let mut res = String::with_capacity(data.len().div_ceil(4) * 5);
+    for chunk in data.chunks(4) {
+        let mut group = [0u8; 4];
+        group[..chunk.len()].copy_from_slice(chunk);
+        let mut num = u32::from_be_bytes(group);
+        if zero && num == 0 && chunk.len() == 4 {
+            res.push('z');
+            continue;
+        }
+        let mut digits = [0u8; 5];
+        for digit in digits.iter_mut().rev() {
+            *digit = alphabet[(num % 85) as usize];
+            num /= 85;
+        }
+        res.extend(digits[..chunk.len() + 1].iter().map(|c| *c as char));
+    }
+    res
+}
+
+fn decode_85(text: &str, alphabet: &[u8], zero: bool) -> anyhow::Result<Vec<u8>> {
+    let mut res = Vec::with_capacity(text.len() / 5 * 4);
+    let mut group = Vec::with_capacity(5);
+    for c in text.bytes().filter(|c| !c.is_ascii_whitespace()) {
+        if zero && c == b'z' && group.is_empty() {
+            res.extend_from_slice(&[0; 4]);
+            continue;
+        }
+        let digit = alphabet
+            .iter()
+            .position(|a| *a == c)
+            .ok_or_else(|| anyhow!("invalid base85 character `{}`", c as char))?;
+        group.push(digit as u64);
+        if group.len() == 5 {
+            res.extend_from_slice(&decode_85_group(&group)?);
+            group.clear();
+        }
+    }
+    match group.len() {
+        0 => {}
+        1 => {
+            return Err(anyhow!(
+                "invalid base85 length, the last group has 1 character"
+            ))
+        }
+        n => {
+            group.resize(5, 84);
+            res.extend_from_slice(&decode_85_group(&group)?[..n - 1]);
+        }
+    }
+    Ok(res)
+}
+
+fn decode_85_group(digits: &[u64]) -> anyhow::Result<[u8; 4]> {
+    let num = digits.iter().fold(0, |acc, digit| acc * 85 + digit);
+    let num = u32::try_from(num).map_err(|_| anyhow!("base85 group overflows"))?;
+    Ok(num.to_be_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codec_vectors() -> anyhow::Result<()> {
+        let cases: [(Codec, &[u8], &str); 9] = [
+            (Codec::Hex, b"hello", "68656c6c6f"),
+            (Codec::HexUpper, b"hello", "68656C6C6F"),
+            (Codec::Base32, b"foobar", "MZXW6YTBOI======"),
+            (Codec::Base32Crockford, b"foobar", "CSQPYRK1E8"),
+            (Codec::Base58, b"hello world", "StV1DL6CwTryKyV"),
+            (Codec::Base58, &[0, 0, 1], "112"),
+            (Codec::Ascii85, b"Man \0\0\0\0", "9jqo^z"),
+            (
+                Codec::Z85,
+                &[0x86, 0x4f, 0xd2, 0x6f, 0xb5, 0x59, 0xf7, 0x5b],
+                "HelloWorld",
+            ),
+            (Codec::Base64Url, &[0xfb, 0xff], "-_8"),
+        ];
+        for (codec, data, text) in cases {
+            assert_eq!(codec_encode(data, codec)?, text, "{codec:?}");
+            assert_eq!(codec_decode(text, codec)?, data, "{codec:?}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_codec_round_trip() -> anyhow::Result<()> {
+        let data = b"\0\x01rcli codec \xff\xfe";
+        for codec in [Codec::Base62, Codec::Ascii85, Codec::Base64] {
+            let text = codec_encode(data, codec)?;
+            assert_eq!(codec_decode(&text, codec)?, data, "{codec:?}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_codec() -> anyhow::Result<()> {
+        let cases = [
+            (Codec::Hex, Some(4), "6865\n6c6c\n6f\n"),
+            (Codec::Base64UrlPad, None, "aGVsbG8=\n"),
+            (Codec::Base64NoPad, Some(4), "aGVs\nbG8\n"),
+        ];
+        for (codec, wrap, text) in cases {
+            let mut res = Vec::new();
+            process_codec_encode(&b"hello"[..], &mut res, codec, wrap)?;
+            assert_eq!(String::from_utf8(res)?, text, "{codec:?}");
+
+            let mut res = Vec::new();
+            process_codec_decode(text.as_bytes(), &mut res, codec)?;
+            assert_eq!(res, b"hello", "{codec:?}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_codec_decode_lenient() -> anyhow::Result<()> {
+        assert_eq!(codec_decode("68656C6c6F\n", Codec::Hex)?, b"hello");
+        assert_eq!(
+            codec_decode("csqp-yrk1-e8", Codec::Base32Crockford)?,
+            b"foobar"
+        );
+        assert_eq!(codec_decode("<~9jqo^~>", Codec::Ascii85)?, b"Man ");
+        assert!(codec_decode("0OIl", Codec::Base58).is_err());
+        assert!(codec_encode(b"abc", Codec::Z85).is_err());
+        Ok(())
+    }
+}
@@ -1,5 +1,6 @@
 use std::fs;
 
+use serde::Serialize;
 use serde_json::Value;
 
 use crate::cli::OutputFormat;
@@ -14,11 +15,16 @@ pub fn process_csv(input: &str, output: &str, format: OutputFormat) -> anyhow::R
         res.push(json_value);
     }
 
-    let content = match format {
-        OutputFormat::Json => serde_json::to_string_pretty(&res)?,
-        OutputFormat::Yaml => serde_yaml::to_string(&res)?,
-    };
-
+    let content = serialize_value(&res, format)?;
     fs::write(output, content)?;
     Ok(())
 }
+
+/// Serialize the value as pretty JSON or YAML.
+pub fn serialize_value(value: &impl Serialize, format: OutputFormat) -> anyhow::Result<String> {
+    let content = match format {
+        OutputFormat::Json => serde_json::to_string_pretty(value)?,
+        OutputFormat::Yaml => serde_yaml::to_string(value)?,
+    };
+    Ok(content)
+}
@@ -0,0 +1,198 @@
+use anyhow::anyhow;
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use url::form_urlencoded;
+
+use crate::cli::UrlMode;
+
+/// Everything but the RFC 3986 unreserved characters, like `encodeURIComponent`.
+const URL_COMPONENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~')
+    .remove(b'!')
+    .remove(b'*')
+    .remove(b'\'')
+    .remove(b'(')
+    .remove(b')');
+
+/// Like `encodeURI`, the characters with a meaning in a URL are kept.
+const URL_FULL: &AsciiSet = &URL_COMPONENT
+    .remove(b';')
+    .remove(b',')
+    .remove(b'/')
+    .remove(b'?')
+    .remove(b':')
+    .remove(b'@')
+    .remove(b'&')
+    .remove(b'=')
+    .remove(b'+')
+    .remove(b'$')
+    .remove(b'#');
+
+/// Named HTML entities that are decoded, the others are left as is.
+const HTML_ENTITIES: &[(&str, char)] = &[
+    ("amp", '&'),
+    ("lt", '<'),
+    ("gt", '>'),
+    ("quot", '"'),
+    ("apos", '\''),
+    ("nbsp", '\u{a0}'),
+    ("copy", '©'),
+    ("reg", '®'),
+    ("trade", '™'),
+    ("hellip", '…'),
+    ("mdash", '—'),
+    ("ndash", '–'),
+    ("laquo", '«'),
+    ("raquo", '»'),
+    ("euro", '€'),
+];
+
+pub fn process_url_escape(input: &str, mode: UrlMode) -> String {
+    match mode {
+        UrlMode::Component => utf8_percent_encode(input, URL_COMPONENT).to_string(),
+        UrlMode::Full => utf8_percent_encode(input, URL_FULL).to_string(),
+        UrlMode::Form => form_urlencoded::byte_serialize(input.as_bytes()).collect(),
+    }
+}
+
+pub fn process_url_unescape(input: &str, mode: UrlMode) -> anyhow::Result<String> {
+    let input = match mode {
+        UrlMode::Form => input.replace('+', " "),
+        UrlMode::Component | UrlMode::Full => input.to_string(),
+    };
+    Ok(percent_decode_str(&input).decode_utf8()?.into_owned())
+}
+
+/// Escape the characters with a meaning in HTML, safe for both text and attribute values.
+pub fn process_html_escape(input: &str) -> String {
+    let mut res = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            '&' => res.push_str("&amp;"),
+            '<' => res.push_str("&lt;"),
+            '>' => res.push_str("&gt;"),
+            '"' => res.push_str("&quot;"),
+            '\'' => res.push_str("&#39;"),
+            c => res.push(c),
+        }
+    }
+    res
+}
+
+/// Decode common named entities and all numeric ones like `&#39;` and `&#x27;`.
+pub fn process_html_unescape(input: &str) -> String {
+    let mut res = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(start) = rest.find('&') {
+        res.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest
+            .find(';')
+            .filter(|end| *end <= 10)
+            .and_then(|end| decode_entity(&rest[1..end]).map(|c| (c, end)));
+        match decoded {
+            Some((c, end)) => {
+                res.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                res.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    res.push_str(rest);
+    res
+}
+
+/// Escape the string as the content of a JSON string, without the surrounding quotes.
+pub fn process_json_escape(input: &str) -> anyhow::Result<String> {
+    let quoted = serde_json::to_string(input)?;
+    Ok(quoted[1..quoted.len() - 1].to_string())
+}
+
+/// Unescape the content of a JSON string, with or without the surrounding quotes.
+pub fn process_json_unescape(input: &str) -> anyhow::Result<String> {
+    let quoted = match input.len() >= 2 && input.starts_with('"') && input.ends_with('"') {
+        true => input.to_string(),
+        false => format!("\"{input}\""),
+    };
+    serde_json::from_str(&quoted).map_err(|e| anyhow!("invalid JSON string, {}", e))
+}
+
+/// Quote the string for POSIX shells. Strings of only safe characters are kept as is,
+/// others are single-quoted with `'` written as `'\''`.
+pub fn process_shell_escape(input: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "_-.,:/@%+=".contains(c);
+    if !input.is_empty() && input.chars().all(safe) {
+        return input.to_string();
+    }
+    format!("'{}'", input.replace('\'', r"'\''"))
+}
+
+fn decode_entity(entity: &str) -> Option<char> {
+    if let Some(num) = entity.strip_prefix('#') {
+        let code = match num.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => num.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    HTML_ENTITIES
+        .iter()
+        .find(|(name, _)| *name == entity)
+        .map(|(_, c)| *c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url_escape() -> anyhow::Result<()> {
+        let input = "a b&c=d/é?";
+        let cases = [
+            (UrlMode::Component, "a%20b%26c%3Dd%2F%C3%A9%3F"),
+            (UrlMode::Full, "a%20b&c=d/%C3%A9?"),
+            (UrlMode::Form, "a+b%26c%3Dd%2F%C3%A9%3F"),
+        ];
+        for (mode, escaped) in cases {
+            assert_eq!(process_url_escape(input, mode), escaped, "{mode:?}");
+            assert_eq!(process_url_unescape(escaped, mode)?, input, "{mode:?}");
+        }
+        assert!(process_url_unescape("%ff", UrlMode::Component).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_html_escape() {
+        let input = r#"<a href="x">Tom & 'Jerry'</a>"#;
+        let escaped = "&lt;a href=&quot;x&quot;&gt;Tom &amp; &#39;Jerry&#39;&lt;/a&gt;";
+        assert_eq!(process_html_escape(input), escaped);
+        assert_eq!(process_html_unescape(escaped), input);
+        assert_eq!(
+            process_html_unescape("&copy; &#x1F600; &unknown; AT&T &"),
+            "© 😀 &unknown; AT&T &"
+        );
+    }
+
+    #[test]
+    fn test_json_escape() -> anyhow::Result<()> {
+        let input = "say \"hi\"\n\tC:\\";
+        let escaped = r#"say \"hi\"\n\tC:\\"#;
+        assert_eq!(process_json_escape(input)?, escaped);
+        assert_eq!(process_json_unescape(escaped)?, input);
+        assert_eq!(process_json_unescape(r#""é""#)?, "é");
+        assert!(process_json_unescape(r#"bad \x"#).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_shell_escape() {
+        assert_eq!(process_shell_escape("file-1.txt"), "file-1.txt");
+        assert_eq!(process_shell_escape("it's a $HOME"), r"'it'\''s a $HOME'");
+        assert_eq!(process_shell_escape(""), "''");
+    }
+}
@@ -1,10 +1,168 @@
-use rand::seq::SliceRandom;
+use anyhow::anyhow;
+use rand::{seq::SliceRandom, Rng};
+use serde::Serialize;
+use std::fmt;
+use zxcvbn::{time_estimates::CrackTimes, zxcvbn};
+
+use crate::{cli::WordCase, utils};
 
 const UPPER: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ";
 const LOWER: &[u8] = b"abcdefghijkmnopqrstuvwxyz";
 const NUMBER: &[u8] = b"123456789";
 const SYMBOL: &[u8] = b"!@#$%^&*_";
 
+/// Letters of pronounceable syllables, `l`, `q` and `y` are left out
+/// as they are ambiguous or hard to pronounce.
+const CONSONANT: &[u8] = b"bcdfghjkmnprstvwxz";
+const VOWEL: &[u8] = b"aeiou";
+
+/// The builtin wordlist for passphrase, the EFF large wordlist of 7776 words.
+const WORDLIST: &str = include_str!("../../assets/eff_large_wordlist.txt");
+
+/// Characters removed from the builtin classes because they are easily confused.
+const AMBIGUOUS: &[u8] = b"IOl0";
+
+/// The kinds of characters a password is made of.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CharClassKind {
+    Upper,
+    Lower,
+    Number,
+    Symbol,
+}
+
+/// A class of characters and the minimum count of them in a password.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CharClass {
+    pub kind: CharClassKind,
+    pub chars: Vec<u8>,
+    pub min: u8,
+}
+
+/// Rules of the characters that a generated password is made of.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenPassPolicy {
+    pub length: u8,
+    pub classes: Vec<CharClass>,
+}
+
+impl GenPassPolicy {
+    /// The builtin policy: each enabled class contributes at least one character.
+    pub fn new(length: u8, upper: bool, lower: bool, number: bool, symbol: bool) -> Self {
+        let classes = [
+            (CharClassKind::Upper, UPPER, upper),
+            (CharClassKind::Lower, LOWER, lower),
+            (CharClassKind::Number, NUMBER, number),
+            (CharClassKind::Symbol, SYMBOL, symbol),
+        ]
+        .into_iter()
+        .filter(|(_, _, enabled)| *enabled)
+        .map(|(kind, chars, _)| CharClass {
+            kind,
+            chars: chars.to_vec(),
+            min: 1,
+        })
+        .collect();
+        Self { length, classes }
+    }
+
+    /// Replace the characters of every enabled class with the ones of `charset`
+    /// belonging to that class. Classes without any character are dropped.
+    pub fn set_charset(&mut self, charset: &str) -> anyhow::Result<()> {
+        let charset = check_ascii(charset, "charset")?;
+        for class in self.classes.iter_mut() {
+            class.chars = charset
+                .iter()
+                .copied()
+                .filter(|c| class_of(*c) == class.kind)
+                .collect();
+            class.chars.sort_unstable();
+            class.chars.dedup();
+        }
+        self.classes.retain(|class| !class.chars.is_empty());
+        Ok(())
+    }
+
+    /// Replace the characters of the symbol class.
+    pub fn set_symbols(&mut self, symbols: &str) -> anyhow::Result<()> {
+        let symbols = check_ascii(symbols, "symbols")?;
+        if let Some(c) = symbols
+            .iter()
+            .find(|c| class_of(**c) != CharClassKind::Symbol)
+        {
+            return Err(anyhow!("`{}` is not a symbol", *c as char));
+        }
+        if let Some(class) = self.class_mut(CharClassKind::Symbol) {
+            class.chars = symbols;
+            class.chars.sort_unstable();
+            class.chars.dedup();
+        }
+        Ok(())
+    }
+
+    /// Add back the ambiguous characters removed from the builtin classes.
+    pub fn include_ambiguous(&mut self) {
+        for c in AMBIGUOUS {
+            if let Some(class) = self.class_mut(class_of(*c)) {
+                if !class.chars.contains(c) {
+                    class.chars.push(*c);
+                }
+            }
+        }
+    }
+
+    /// Remove the given characters from all classes. Classes left without any
+    /// character are dropped.
+    pub fn exclude(&mut self, chars: &str) {
+        for class in self.classes.iter_mut() {
+            class.chars.retain(|c| !chars.as_bytes().contains(c));
+        }
+        self.classes.retain(|class| !class.chars.is_empty());
+    }
+
+    /// Require at least `min` characters of the class `kind`.
+    pub fn set_min(&mut self, kind: CharClassKind, min: u8) -> anyhow::Result<()> {
+        match self.class_mut(kind) {
+            Some(class) => class.min = min,
+            None if min == 0 => {}
+            None => {
+                return Err(anyhow!(
+                    "cannot require {min} {kind} character(s): {kind} class is disabled or empty"
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that a password can be generated from the policy.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.classes.is_empty() {
+            return Err(anyhow!("no characters available to generate the password"));
+        }
+        let required: usize = self.classes.iter().map(|class| class.min as usize).sum();
+        if required > self.length as usize {
+            return Err(anyhow!(
+                "length {} is too short, the minimum counts of classes require at least {required} characters",
+                self.length
+            ));
+        }
+        Ok(())
+    }
+
+    /// Entropy bits of a password generated from the policy.
+    pub fn entropy(&self) -> f64 {
+        let size: usize = self.classes.iter().map(|class| class.chars.len()).sum();
+        if size == 0 {
+            return 0.0;
+        }
+        self.length as f64 * (size as f64).log2()
+    }
+
+    fn class_mut(&mut self, kind: CharClassKind) -> Option<&mut CharClass> {
+        self.classes.iter_mut().find(|class| class.kind == kind)
+    }
+}
+
 pub fn process_genpass(
     length: u8,
     upper: bool,
@@ -12,28 +170,26 @@ pub fn process_genpass(
     number: bool,
     symbol: bool,
 ) -> anyhow::Result<String> {
-    let mut rng = rand::thread_rng();
-    let mut password = Vec::new();
+    let policy = GenPassPolicy::new(length, upper, lower, number, symbol);
+    process_genpass_with_policy(&policy)
+}
+
+pub fn process_genpass_with_policy(policy: &GenPassPolicy) -> anyhow::Result<String> {
+    policy.validate()?;
+
+    let mut rng = utils::rng();
+    let mut password = Vec::with_capacity(policy.length as usize);
     let mut chars = Vec::new();
 
-    if upper {
-        chars.extend_from_slice(UPPER);
-        password.push(*UPPER.choose(&mut rng).expect("UPPER won't be empty"));
-    }
-    if lower {
-        chars.extend_from_slice(LOWER);
-        password.push(*LOWER.choose(&mut rng).expect("LOWER won't be empty"));
-    }
-    if number {
-        chars.extend_from_slice(NUMBER);
-        password.push(*NUMBER.choose(&mut rng).expect("NUMBER won't be empty"));
-    }
-    if symbol {
-        chars.extend_from_slice(SYMBOL);
-        password.push(*SYMBOL.choose(&mut rng).expect("SYMBOL won't be empty"))
+    for class in &policy.classes {
+        chars.extend_from_slice(&class.chars);
+        for _ in 0..class.min {
+            let c = class.chars.choose(&mut rng).expect("class won't be empty");
+            password.push(*c);
+        }
     }
 
-    for _ in 0..length - password.len() as u8 {
+    while password.len() < policy.length as usize {
         let c = chars
             .choose(&mut rng)
             .expect("chars won't be empty in this context");
@@ -43,3 +199,471 @@ pub fn process_genpass(
     password.shuffle(&mut rng);
     Ok(String::from_utf8(password)?)
 }
+
+/// Generate a diceware-like passphrase by picking `words` words from `wordlist`,
+/// optionally injecting a digit and a symbol into random words.
+pub fn process_genphrase(
+    wordlist: &[impl AsRef<str>],
+    words: u8,
+    separator: &str,
+    case: WordCase,
+    number: bool,
+    symbol: bool,
+) -> anyhow::Result<String> {
+    if wordlist.len() < 2 {
+        return Err(anyhow!("wordlist must contain at least 2 words"));
+    }
+    if words == 0 {
+        return Err(anyhow!("passphrase must contain at least 1 word"));
+    }
+
+    let mut rng = utils::rng();
+    let mut phrase = Vec::with_capacity(words as usize);
+    for _ in 0..words {
+        let word = wordlist
+            .choose(&mut rng)
+            .expect("wordlist won't be empty")
+            .as_ref();
+        let word = match case {
+            WordCase::Lower => word.to_lowercase(),
+            WordCase::Upper => word.to_uppercase(),
+            WordCase::Title => capitalize(word),
+            WordCase::Random => match rng.gen_bool(0.5) {
+                true => capitalize(word),
+                false => word.to_lowercase(),
+            },
+        };
+        phrase.push(word);
+    }
+
+    if number {
+        let idx = rng.gen_range(0..phrase.len());
+        let c = *NUMBER.choose(&mut rng).expect("NUMBER won't be empty");
+        phrase[idx].push(c as char);
+    }
+    if symbol {
+        let idx = rng.gen_range(0..phrase.len());
+        let c = *SYMBOL.choose(&mut rng).expect("SYMBOL won't be empty");
+        phrase[idx].push(c as char);
+    }
+
+    Ok(phrase.join(separator))
+}
+
+/// Load a wordlist, either the builtin one or from the given content.
+///
+/// Both plain lists (one word per line) and EFF diceware lists
+/// (`11111\tabacus`) are supported. Words are mapped to the `case` first, then
+/// duplicated ones are removed so the entropy estimate stays honest.
+pub fn load_wordlist(content: Option<&str>, case: WordCase) -> Vec<String> {
+    let content = content.unwrap_or(WORDLIST);
+    let mut words: Vec<String> = content
+        .lines()
+        .filter_map(|line| line.split_whitespace().last())
+        .map(|word| match case {
+            WordCase::Upper => word.to_uppercase(),
+            WordCase::Title => capitalize(word),
+            // random case picks between title and lower case of the word
+            WordCase::Lower | WordCase::Random => word.to_lowercase(),
+        })
+        .collect();
+    words.sort_unstable();
+    words.dedup();
+    words
+}
+
+/// Entropy bits of a passphrase generated by [`process_genphrase`].
+pub fn genphrase_entropy(
+    wordlist_size: usize,
+    words: u8,
+    case: WordCase,
+    number: bool,
+    symbol: bool,
+) -> f64 {
+    let words = words as f64;
+    let mut bits = words * (wordlist_size as f64).log2();
+    if case == WordCase::Random {
+        bits += words;
+    }
+    if number {
+        bits += (NUMBER.len() as f64).log2() + words.log2();
+    }
+    if symbol {
+        bits += (SYMBOL.len() as f64).log2() + words.log2();
+    }
+    bits
+}
+
+/// Generate a pronounceable password made of consonant-vowel syllables like `Kavu`,
+/// optionally injecting a digit and a symbol between syllables.
+///
+/// With both `upper` and `lower` the first letter of each syllable is randomly
+/// capitalized, with only one of them all letters use that case.
+pub fn process_genpronounceable(
+    length: u8,
+    upper: bool,
+    lower: bool,
+    number: bool,
+    symbol: bool,
+) -> anyhow::Result<String> {
+    let letters = pronounceable_letters(length, upper, lower, number, symbol)?;
+
+    let mut rng = utils::rng();
+    let mut tokens = Vec::with_capacity(letters.div_ceil(2) + 2);
+    for i in (0..letters).step_by(2) {
+        let mut syllable = String::with_capacity(2);
+        syllable.push(
+            *CONSONANT
+                .choose(&mut rng)
+                .expect("CONSONANT won't be empty") as char,
+        );
+        if i + 1 < letters {
+            syllable.push(*VOWEL.choose(&mut rng).expect("VOWEL won't be empty") as char);
+        }
+        let syllable = match (upper, lower) {
+            (true, true) if rng.gen_bool(0.5) => capitalize(&syllable),
+            (true, false) => syllable.to_uppercase(),
+            _ => syllable,
+        };
+        tokens.push(syllable);
+    }
+
+    if number {
+        let idx = rng.gen_range(0..=tokens.len());
+        let c = *NUMBER.choose(&mut rng).expect("NUMBER won't be empty");
+        tokens.insert(idx, (c as char).to_string());
+    }
+    if symbol {
+        let idx = rng.gen_range(0..=tokens.len());
+        let c = *SYMBOL.choose(&mut rng).expect("SYMBOL won't be empty");
+        tokens.insert(idx, (c as char).to_string());
+    }
+
+    Ok(tokens.concat())
+}
+
+/// Entropy bits of a password generated by [`process_genpronounceable`].
+///
+/// It's much lower than a random password of the same length, since each
+/// letter is picked from a consonant or vowel set only.
+pub fn genpronounceable_entropy(
+    length: u8,
+    upper: bool,
+    lower: bool,
+    number: bool,
+    symbol: bool,
+) -> f64 {
+    let Ok(letters) = pronounceable_letters(length, upper, lower, number, symbol) else {
+        return 0.0;
+    };
+    let syllables = letters.div_ceil(2);
+    let mut bits = syllables as f64 * (CONSONANT.len() as f64).log2()
+        + (letters / 2) as f64 * (VOWEL.len() as f64).log2();
+    if upper && lower {
+        bits += syllables as f64;
+    }
+    if number {
+        bits += (NUMBER.len() as f64).log2() + ((syllables + 1) as f64).log2();
+    }
+    if symbol {
+        let tokens = syllables + number as usize;
+        bits += (SYMBOL.len() as f64).log2() + ((tokens + 1) as f64).log2();
+    }
+    bits
+}
+
+/// Number of letters in a pronounceable password, the rest are the digit and symbol.
+fn pronounceable_letters(
+    length: u8,
+    upper: bool,
+    lower: bool,
+    number: bool,
+    symbol: bool,
+) -> anyhow::Result<usize> {
+    if !upper && !lower {
+        return Err(anyhow!(
+            "pronounceable password needs uppercase or lowercase letters"
+        ));
+    }
+    let letters = (length as usize).saturating_sub(number as usize + symbol as usize);
+    if letters < 2 {
+        return Err(anyhow!(
+            "length {length} is too short for a pronounceable password"
+        ));
+    }
+    Ok(letters)
+}
+
+/// The strength report of a generated password.
+#[derive(Debug, Serialize)]
+pub struct PasswordReport {
+    pub password: String,
+    pub length: usize,
+    pub classes: Vec<&'static str>,
+    pub entropy: f64,
+    /// The password was regenerated until it passed filters like a minimum score,
+    /// which rejects part of the space, so the real entropy is lower than `entropy`.
+    pub entropy_upper_bound: bool,
+    pub score: u8,
+    pub guesses: u64,
+    pub crack_time: CrackTime,
+}
+
+/// Estimated crack time of a password under several attack scenarios.
+#[derive(Debug, Serialize)]
+pub struct CrackTime {
+    pub online_throttling: String,
+    pub online_no_throttling: String,
+    pub offline_slow_hashing: String,
+    pub offline_fast_hashing: String,
+}
+
+impl PasswordReport {
+    /// Estimate the strength of the password, `user_inputs` are words
+    /// like company names that an attacker would try first. The word `separator`
+    /// of a passphrase isn't counted as a character class.
+    pub fn new(
+        password: String,
+        entropy: f64,
+        user_inputs: &[&str],
+        separator: &str,
+    ) -> anyhow::Result<Self> {
+        let estimate = zxcvbn(&password, user_inputs).map_err(|e| anyhow!("{}", e))?;
+        let classes = match separator {
+            "" => charset_classes(&password),
+            separator => charset_classes(&password.replace(separator, "")),
+        };
+        Ok(Self {
+            length: password.chars().count(),
+            classes,
+            entropy: (entropy * 100.0).round() / 100.0,
+            entropy_upper_bound: false,
+            score: estimate.score(),
+            guesses: estimate.guesses(),
+            crack_time: CrackTime::new(estimate.crack_times()),
+            password,
+        })
+    }
+}
+
+impl CrackTime {
+    pub fn new(times: CrackTimes) -> Self {
+        Self {
+            online_throttling: times.online_throttling_100_per_hour().to_string(),
+            online_no_throttling: times.online_no_throttling_10_per_second().to_string(),
+            offline_slow_hashing: times.offline_slow_hashing_1e4_per_second().to_string(),
+            offline_fast_hashing: times.offline_fast_hashing_1e10_per_second().to_string(),
+        }
+    }
+}
+
+/// Find the first forbidden word contained in the password, ignoring case.
+pub fn find_forbidden<'a>(password: &str, forbidden: &[&'a str]) -> Option<&'a str> {
+    let password = password.to_lowercase();
+    forbidden
+        .iter()
+        .find(|word| !word.is_empty() && password.contains(&word.to_lowercase()))
+        .copied()
+}
+
+/// The character classes used by the password.
+pub fn charset_classes(password: &str) -> Vec<&'static str> {
+    let mut classes = Vec::new();
+    if password.chars().any(|c| c.is_uppercase()) {
+        classes.push(CharClassKind::Upper.name());
+    }
+    if password.chars().any(|c| c.is_lowercase()) {
+        classes.push(CharClassKind::Lower.name());
+    }
+    if password.chars().any(|c| c.is_ascii_digit()) {
+        classes.push(CharClassKind::Number.name());
+    }
+    if password.chars().any(|c| !c.is_alphanumeric()) {
+        classes.push(CharClassKind::Symbol.name());
+    }
+    classes
+}
+
+impl CharClassKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CharClassKind::Upper => "upper",
+            CharClassKind::Lower => "lower",
+            CharClassKind::Number => "number",
+            CharClassKind::Symbol => "symbol",
+        }
+    }
+}
+
+impl fmt::Display for CharClassKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+fn class_of(c: u8) -> CharClassKind {
+    match c {
+        b'A'..=b'Z' => CharClassKind::Upper,
+        b'a'..=b'z' => CharClassKind::Lower,
+        b'0'..=b'9' => CharClassKind::Number,
+        _ => CharClassKind::Symbol,
+    }
+}
+
+fn check_ascii(chars: &str, name: &str) -> anyhow::Result<Vec<u8>> {
+    match chars.bytes().find(|c| !c.is_ascii_graphic()) {
+        Some(_) => Err(anyhow!("`{name}` only supports printable ASCII characters")),
+        None => Ok(chars.as_bytes().to_vec()),
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_wordlist() {
+        let words = load_wordlist(None, WordCase::Lower);
+        assert_eq!(words.len(), 7776);
+
+        let content = "11111\tabacus\n11112\tabdomen\n11113\tabacus\n";
+        let words = load_wordlist(Some(content), WordCase::Lower);
+        assert_eq!(words, vec!["abacus", "abdomen"]);
+
+        let words = load_wordlist(Some("Apple\napple\nAPPLE\nbanana\n"), WordCase::Title);
+        assert_eq!(words, vec!["Apple", "Banana"]);
+    }
+
+    #[test]
+    fn test_process_genphrase() -> anyhow::Result<()> {
+        let wordlist = ["alpha", "bravo", "charlie"];
+        let phrase = process_genphrase(&wordlist, 4, "-", WordCase::Title, false, false)?;
+        let words: Vec<&str> = phrase.split('-').collect();
+        assert_eq!(words.len(), 4);
+        assert!(words
+            .iter()
+            .all(|w| ["Alpha", "Bravo", "Charlie"].contains(w)));
+
+        let phrase = process_genphrase(&wordlist, 3, " ", WordCase::Lower, true, true)?;
+        assert!(phrase.bytes().any(|c| NUMBER.contains(&c)));
+        assert!(phrase.bytes().any(|c| SYMBOL.contains(&c)));
+
+        assert!(process_genphrase(&wordlist, 0, "-", WordCase::Lower, false, false).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_entropy() {
+        assert_eq!(
+            GenPassPolicy::new(16, false, false, false, false).entropy(),
+            0.0
+        );
+        assert_eq!(
+            GenPassPolicy::new(10, false, false, true, false).entropy(),
+            10.0 * 9f64.log2()
+        );
+        // 6 * log2(7776) = 6 * log2(6^5)
+        let bits = genphrase_entropy(7776, 6, WordCase::Lower, false, false);
+        assert!((bits - 30.0 * 6f64.log2()).abs() < 1e-9);
+        let bits = genphrase_entropy(7776, 6, WordCase::Random, false, false);
+        assert!((bits - 30.0 * 6f64.log2() - 6.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_process_genpronounceable() -> anyhow::Result<()> {
+        let password = process_genpronounceable(12, false, true, false, false)?;
+        assert_eq!(password.len(), 12);
+        for (i, c) in password.bytes().enumerate() {
+            match i % 2 {
+                0 => assert!(CONSONANT.contains(&c)),
+                _ => assert!(VOWEL.contains(&c)),
+            }
+        }
+
+        let password = process_genpronounceable(13, true, true, true, true)?;
+        assert_eq!(password.len(), 13);
+        assert_eq!(password.bytes().filter(u8::is_ascii_digit).count(), 1);
+        assert_eq!(password.bytes().filter(|c| SYMBOL.contains(c)).count(), 1);
+
+        assert!(process_genpronounceable(3, true, true, true, true).is_err());
+        assert!(process_genpronounceable(12, false, false, true, true).is_err());
+
+        // 6 syllables: 6 * log2(18 * 5)
+        let bits = genpronounceable_entropy(12, false, true, false, false);
+        assert!((bits - 6.0 * 90f64.log2()).abs() < 1e-9);
+        assert!(bits < GenPassPolicy::new(12, false, true, false, false).entropy());
+        Ok(())
+    }
+
+    #[test]
+    fn test_password_report() -> anyhow::Result<()> {
+        let report = PasswordReport::new("abc-Def7".to_string(), 40.0, &[], "")?;
+        assert_eq!(report.length, 8);
+        assert_eq!(report.classes, vec!["upper", "lower", "number", "symbol"]);
+        assert!(report.score <= 4);
+        assert!(PasswordReport::new(String::new(), 0.0, &[], "").is_err());
+
+        let report = PasswordReport::new("correct-horse-battery".to_string(), 33.0, &[], "-")?;
+        assert_eq!(report.classes, vec!["lower"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_genpass_with_policy() -> anyhow::Result<()> {
+        let mut policy = GenPassPolicy::new(12, true, true, true, true);
+        policy.set_symbols("-+")?;
+        policy.set_min(CharClassKind::Number, 4)?;
+        policy.exclude("ABCDEFGHJKLMNPQRSTUVWXY");
+        let password = process_genpass_with_policy(&policy)?;
+        assert_eq!(password.len(), 12);
+        assert!(password.bytes().filter(u8::is_ascii_digit).count() >= 4);
+        assert!(password.contains('Z'));
+        assert!(password.contains(['-', '+']));
+
+        let mut policy = GenPassPolicy::new(8, true, true, true, false);
+        policy.set_charset("ab12!")?;
+        assert_eq!(policy.classes.len(), 2);
+        let password = process_genpass_with_policy(&policy)?;
+        assert!(password.bytes().all(|c| b"ab12".contains(&c)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_gen_pass_policy_validate() -> anyhow::Result<()> {
+        assert!(process_genpass(3, true, true, true, true).is_err());
+        assert!(process_genpass(4, true, true, true, true).is_ok());
+        assert!(process_genpass(4, false, false, false, false).is_err());
+
+        let mut policy = GenPassPolicy::new(8, true, true, true, true);
+        assert!(policy.set_symbols("ab").is_err());
+        policy.set_min(CharClassKind::Upper, 5)?;
+        policy.set_min(CharClassKind::Lower, 5)?;
+        assert!(policy.validate().is_err());
+
+        let mut policy = GenPassPolicy::new(8, true, true, false, true);
+        assert!(policy.set_min(CharClassKind::Number, 2).is_err());
+        policy.include_ambiguous();
+        assert!(policy.classes[0].chars.contains(&b'O'));
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_forbidden() {
+        let forbidden = ["acme", "Initech"];
+        assert_eq!(find_forbidden("x-ACME-y", &forbidden), Some("acme"));
+        assert_eq!(find_forbidden("initech42", &forbidden), Some("Initech"));
+        assert_eq!(find_forbidden("hunter2", &forbidden), None);
+        assert_eq!(find_forbidden("hunter2", &[""]), None);
+    }
+}
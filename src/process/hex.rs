@@ -0,0 +1,201 @@
+use std::io::{self, Read, Write};
+
+use anyhow::anyhow;
+use colored::{Color, Colorize};
+
+/// The largest gap between offsets that [`process_hex_reverse`] fills with zeros.
+const MAX_REVERSE_GAP: usize = 64 * 1024 * 1024;
+
+/// Layout of a hexdump.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HexDumpOptions {
+    /// Bytes per line.
+    pub width: usize,
+    /// Bytes per group of hex digits, `0` for no grouping.
+    pub group: usize,
+    /// Color bytes by class: null, printable, whitespace, other ASCII and non-ASCII.
+    pub color: bool,
+}
+
+impl Default for HexDumpOptions {
+    /// The `xxd` layout.
+    fn default() -> Self {
+        Self {
+            width: 16,
+            group: 2,
+            color: false,
+        }
+    }
+}
+
+/// Stream the input as a hexdump, starting at `offset` and stopping after `length` bytes.
+pub fn process_hex_dump(
+    mut reader: impl Read,
+    mut writer: impl Write,
+    offset: u64,
+    length: Option<u64>,
+    opts: &HexDumpOptions,
+) -> anyhow::Result<()> {
+    if opts.width == 0 {
+        return Err(anyhow!("width must be greater than 0"));
+    }
+    let skipped = io::copy(&mut (&mut reader).take(offset), &mut io::sink())?;
+    let mut reader = reader.take(length.unwrap_or(u64::MAX));
+    let mut line = vec![0u8; opts.width];
+    let mut pos = skipped;
+    loop {
+        let n = read_full(&mut reader, &mut line)?;
+        if n == 0 {
+            break;
+        }
+        writeln!(writer, "{}", format_line(pos, &line[..n], opts))?;
+        pos += n as u64;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Render the bytes as a hexdump starting at offset 0.
+pub fn hexdump(data: &[u8], opts: &HexDumpOptions) -> String {
+    data.chunks(opts.width)
+        .enumerate()
+        .map(|(i, line)| format_line((i * opts.width) as u64, line, opts) + "\n")
+        .collect()
+}
+
+/// Turn a hexdump back into bytes. Lines like `00000010: 4865 6c6c  He..` are written
+/// at their offset, padding gaps of up to 64 MiB with zeros, and lines without an offset
+/// are plain hex.
+pub fn process_hex_reverse(text: &str) -> anyhow::Result<Vec<u8>> {
+    let mut res = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let hex = match line.split_once(": ") {
+            Some((offset, rest)) => {
+                let offset = u64::from_str_radix(offset.trim(), 16)
+                    .map_err(|_| anyhow!("invalid offset at line {}", i + 1))?;
+                let offset = usize::try_from(offset)?;
+                if offset < res.len() {
+                    return Err(anyhow!("offset goes backwards at line {}", i + 1));
+                }
+                if offset - res.len() > MAX_REVERSE_GAP {
+                    return Err(anyhow!(
+                        "offset {offset:#x} at line {} leaves a gap larger than 64 MiB",
+                        i + 1
+                    ));
+                }
+                res.resize(offset, 0);
+                // the hex and ASCII columns are separated by two spaces
+                let rest = rest.trim_start();
+                rest.split_once("  ").map_or(rest, |(hex, _)| hex)
+            }
+            None => line,
+        };
+        let digits: String = hex.split_whitespace().collect();
+        if !digits.len().is_multiple_of(2) {
+            return Err(anyhow!("odd number of hex digits at line {}", i + 1));
+        }
+        for pair in digits.as_bytes().chunks(2) {
+            let byte = std::str::from_utf8(pair)
+                .ok()
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or_else(|| anyhow!("invalid hex digits at line {}", i + 1))?;
+            res.push(byte);
+        }
+    }
+    Ok(res)
+}
+
+fn format_line(offset: u64, bytes: &[u8], opts: &HexDumpOptions) -> String {
+    let mut hex = String::new();
+    for i in 0..opts.width {
+        if i > 0 && opts.group > 0 && i % opts.group == 0 {
+            hex.push(' ');
+        }
+        match bytes.get(i) {
+            Some(b) => hex.push_str(&paint(&format!("{b:02x}"), *b, opts.color)),
+            None => hex.push_str("  "),
+        }
+    }
+    let ascii: String = bytes
+        .iter()
+        .map(|b| {
+            let c = match b.is_ascii_graphic() || *b == b' ' {
+                true => *b as char,
+                false => '.',
+            };
+            paint(&c.to_string(), *b, opts.color)
+        })
+        .collect();
+    format!("{offset:08x}: {hex}  {ascii}")
+}
+
+fn paint(s: &str, byte: u8, color: bool) -> String {
+    if !color {
+        return s.to_string();
+    }
+    let color = match byte {
+        0 => Color::BrightBlack,
+        b if b.is_ascii_graphic() => Color::Cyan,
+        b if b.is_ascii_whitespace() => Color::Green,
+        b if b.is_ascii() => Color::Magenta,
+        _ => Color::Yellow,
+    };
+    s.color(color).to_string()
+}
+
+/// Read until the buffer is full or EOF, returning the bytes read.
+fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut n = 0;
+    while n < buf.len() {
+        match reader.read(&mut buf[n..])? {
+            0 => break,
+            read => n += read,
+        }
+    }
+    Ok(n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DATA: &[u8] = b"Hello, world!\n\x00\xff rcli";
+
+    #[test]
+    fn test_hexdump() {
+        assert_eq!(
+            hexdump(DATA, &HexDumpOptions::default()),
+            "00000000: 4865 6c6c 6f2c 2077 6f72 6c64 210a 00ff  Hello, world!...\n\
+             00000010: 2072 636c 69                              rcli\n"
+        );
+    }
+
+    #[test]
+    fn test_process_hex_dump() -> anyhow::Result<()> {
+        let opts = HexDumpOptions {
+            width: 4,
+            group: 0,
+            color: false,
+        };
+        let mut res = Vec::new();
+        process_hex_dump(DATA, &mut res, 7, Some(6), &opts)?;
+        assert_eq!(
+            String::from_utf8(res)?,
+            "00000007: 776f726c  worl\n0000000b: 6421      d!\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_hex_reverse() -> anyhow::Result<()> {
+        let dump = hexdump(DATA, &HexDumpOptions::default());
+        assert_eq!(process_hex_reverse(&dump)?, DATA);
+
+        let sparse = "00000000: 4142  AB\n00000004: 43  C\n";
+        assert_eq!(process_hex_reverse(sparse)?, b"AB\0\0C");
+        assert_eq!(process_hex_reverse("48 65\n6c6c6f\n")?, b"Hello");
+        assert!(process_hex_reverse("00000000: 4g  .").is_err());
+        assert!(process_hex_reverse("ffffffffffff: 00").is_err());
+        Ok(())
+    }
+}
@@ -1,10 +1,21 @@
 pub mod b64;
+pub mod cbor;
+pub mod codec;
 pub mod csv_convert;
+pub mod escape;
 pub mod gen_pass;
+pub mod hex;
 pub mod http_serve;
 pub mod jwt;
+pub mod msgpack;
+pub mod otp;
+pub mod pass_check;
+pub mod pass_derive;
+pub mod passwd;
+pub mod proto;
 pub mod text;
 pub mod time;
+pub mod token;
 
 use std::io::Read;
 
@@ -12,8 +23,10 @@ pub use b64::{process_decode, process_encode};
 pub use csv_convert::process_csv;
 pub use gen_pass::process_genpass;
 pub use http_serve::process_http_serve;
+pub use pass_check::process_pass_check;
+pub use pass_derive::process_genpass_derive;
 pub use text::{process_text_gen_key, process_text_sign, process_text_verify};
-pub use time::process_unix_to_string;
+pub use time::process_unix_to_datetime;
 
 use crate::utils;
 
@@ -0,0 +1,137 @@
+use anyhow::anyhow;
+use base64::prelude::*;
+use rmpv::Value;
+use serde_json::{Map, Number, Value as JsonValue};
+
+use super::{codec::get_codec_input, csv_convert::serialize_value, get_input};
+use crate::cli::{Codec, OutputFormat};
+
+/// Decode a MessagePack value into pretty JSON or YAML, see [`msgpack_to_json`] for the mapping.
+pub fn process_msgpack_decode(
+    input: &str,
+    codec: Option<Codec>,
+    format: OutputFormat,
+) -> anyhow::Result<String> {
+    let value = read_msgpack(&get_codec_input(input, codec)?)?;
+    serialize_value(&msgpack_to_json(&value), format)
+}
+
+/// Encode a JSON document as MessagePack.
+pub fn process_msgpack_encode(input: &str) -> anyhow::Result<Vec<u8>> {
+    let value: JsonValue = serde_json::from_slice(&get_input(input)?)?;
+    let mut buf = Vec::new();
+    rmpv::encode::write_value(&mut buf, &json_to_msgpack(&value))?;
+    Ok(buf)
+}
+
+/// Read exactly one MessagePack value.
+pub fn read_msgpack(data: &[u8]) -> anyhow::Result<Value> {
+    let mut rest = data;
+    let value =
+        rmpv::decode::read_value(&mut rest).map_err(|e| anyhow!("invalid MessagePack, {}", e))?;
+    if !rest.is_empty() {
+        return Err(anyhow!(
+            "{} trailing bytes after the MessagePack value",
+            rest.len()
+        ));
+    }
+    Ok(value)
+}
+
+/// Convert MessagePack to JSON the way [`super::cbor::cbor_to_json`] does: binary becomes
+/// unpadded base64url and non-finite floats become null. An extension becomes
+/// `{"ext": type, "data": base64url}` and map keys that aren't strings are written as JSON.
+pub fn msgpack_to_json(value: &Value) -> JsonValue {
+    match value {
+        Value::Nil => JsonValue::Null,
+        Value::Boolean(b) => JsonValue::Bool(*b),
+        Value::Integer(i) => match (i.as_u64(), i.as_i64()) {
+            (Some(u), _) => JsonValue::from(u),
+            (_, Some(i)) => JsonValue::from(i),
+            _ => JsonValue::Null,
+        },
+        Value::F32(f) => float_to_json(*f as f64),
+        Value::F64(f) => float_to_json(*f),
+        Value::String(s) => match s.as_str() {
+            Some(s) => JsonValue::String(s.to_string()),
+            None => JsonValue::String(String::from_utf8_lossy(s.as_bytes()).into_owned()),
+        },
+        Value::Binary(bytes) => JsonValue::String(BASE64_URL_SAFE_NO_PAD.encode(bytes)),
+        Value::Array(items) => items.iter().map(msgpack_to_json).collect(),
+        Value::Map(entries) => {
+            let map: Map<String, JsonValue> = entries
+                .iter()
+                .map(|(k, v)| {
+                    let key = match msgpack_to_json(k) {
+                        JsonValue::String(s) => s,
+                        k => k.to_string(),
+                    };
+                    (key, msgpack_to_json(v))
+                })
+                .collect();
+            JsonValue::Object(map)
+        }
+        Value::Ext(ty, data) => serde_json::json!({
+            "ext": ty,
+            "data": BASE64_URL_SAFE_NO_PAD.encode(data),
+        }),
+    }
+}
+
+/// Convert JSON to MessagePack, integers use the smallest encoding that fits.
+pub fn json_to_msgpack(value: &JsonValue) -> Value {
+    match value {
+        JsonValue::Null => Value::Nil,
+        JsonValue::Bool(b) => Value::Boolean(*b),
+        JsonValue::Number(n) => match (n.as_u64(), n.as_i64(), n.as_f64()) {
+            (Some(u), _, _) => Value::from(u),
+            (_, Some(i), _) => Value::from(i),
+            (_, _, f) => Value::F64(f.unwrap_or(f64::NAN)),
+        },
+        JsonValue::String(s) => Value::from(s.as_str()),
+        JsonValue::Array(items) => Value::Array(items.iter().map(json_to_msgpack).collect()),
+        JsonValue::Object(map) => Value::Map(
+            map.iter()
+                .map(|(k, v)| (Value::from(k.as_str()), json_to_msgpack(v)))
+                .collect(),
+        ),
+    }
+}
+
+fn float_to_json(f: f64) -> JsonValue {
+    Number::from_f64(f).map_or(JsonValue::Null, JsonValue::Number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_msgpack_to_json() -> anyhow::Result<()> {
+        // {"a": 1, "b": [true, nil, -2.5], 1: bin 0102, "e": ext(-1, 00)}
+        let data = data_encoding::HEXLOWER.decode(
+            b"84a16101a16293c3c0cbc004000000000000\
+              01c4020102a165d4ff00",
+        )?;
+        assert_eq!(
+            msgpack_to_json(&read_msgpack(&data)?),
+            serde_json::json!({
+                "a": 1,
+                "b": [true, null, -2.5],
+                "1": "AQI",
+                "e": {"ext": -1, "data": "AA"},
+            })
+        );
+        assert!(read_msgpack(&[0x01, 0x02]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_to_msgpack() -> anyhow::Result<()> {
+        let json = serde_json::json!({"id": 300, "neg": -1, "pi": 3.5, "tags": ["x"], "ok": null});
+        let mut buf = Vec::new();
+        rmpv::encode::write_value(&mut buf, &json_to_msgpack(&json))?;
+        assert_eq!(msgpack_to_json(&read_msgpack(&buf)?), json);
+        Ok(())
+    }
+}
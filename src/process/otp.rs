@@ -0,0 +1,365 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::anyhow;
+use data_encoding::BASE32_NOPAD;
+use hmac::{digest::KeyInit, Hmac, Mac};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use rand::RngCore;
+use sha1::Sha1;
+use sha2::{Sha256, Sha512};
+use url::Url;
+
+use crate::{
+    cli::{OtpAlgorithm, OtpType},
+    utils,
+};
+
+/// Characters escaped in the label and issuer of an otpauth uri.
+const URI_COMPONENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~')
+    .remove(b'@');
+
+/// Parameters of a one-time password, as stored in an `otpauth://` URI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OtpParams {
+    pub kind: OtpType,
+    pub secret: Vec<u8>,
+    pub algorithm: OtpAlgorithm,
+    pub digits: u8,
+    /// Time step in seconds, only for TOTP.
+    pub period: u64,
+    /// Moving factor, only for HOTP.
+    pub counter: u64,
+    pub issuer: Option<String>,
+    pub account: Option<String>,
+}
+
+impl OtpParams {
+    pub fn try_new(
+        kind: OtpType,
+        secret: Vec<u8>,
+        algorithm: OtpAlgorithm,
+        digits: u8,
+        period: u64,
+        counter: u64,
+    ) -> anyhow::Result<Self> {
+        let params = Self {
+            kind,
+            secret,
+            algorithm,
+            digits,
+            period,
+            counter,
+            issuer: None,
+            account: None,
+        };
+        params.validate()?;
+        Ok(params)
+    }
+
+    /// Parse an `otpauth://totp/Issuer:account?secret=...&issuer=...` URI.
+    pub fn from_uri(uri: &str) -> anyhow::Result<Self> {
+        let url = Url::parse(uri)?;
+        if url.scheme() != "otpauth" {
+            return Err(anyhow!("invalid otpauth uri, scheme must be `otpauth`"));
+        }
+        let kind = match url.host_str() {
+            Some("totp") => OtpType::Totp,
+            Some("hotp") => OtpType::Hotp,
+            _ => {
+                return Err(anyhow!(
+                    "invalid otpauth uri, type must be `totp` or `hotp`"
+                ))
+            }
+        };
+
+        let label = percent_decode_str(url.path().trim_start_matches('/')).decode_utf8()?;
+        let (mut issuer, account) = match label.split_once(':') {
+            Some((issuer, account)) => (Some(issuer.trim().to_string()), account.trim()),
+            None => (None, label.trim()),
+        };
+        let account = (!account.is_empty()).then(|| account.to_string());
+
+        let mut secret = None;
+        let mut algorithm = OtpAlgorithm::Sha1;
+        let mut digits = 6;
+        let mut period = 30;
+        let mut counter = None;
+        for (key, value) in url.query_pairs() {
+            match key.as_ref() {
+                "secret" => secret = Some(decode_secret(&value)?),
+                "issuer" => issuer = Some(value.to_string()),
+                "algorithm" => {
+                    algorithm = match value.to_uppercase().as_str() {
+                        "SHA1" => OtpAlgorithm::Sha1,
+                        "SHA256" => OtpAlgorithm::Sha256,
+                        "SHA512" => OtpAlgorithm::Sha512,
+                        _ => return Err(anyhow!("unsupported otp algorithm: {}", value)),
+                    }
+                }
+                "digits" => digits = value.parse()?,
+                "period" => period = value.parse()?,
+                "counter" => counter = Some(value.parse()?),
+                _ => {}
+            }
+        }
+
+        let secret = secret.ok_or_else(|| anyhow!("invalid otpauth uri, missing secret"))?;
+        let counter = match (kind, counter) {
+            (OtpType::Hotp, None) => {
+                return Err(anyhow!("invalid otpauth uri, missing counter for hotp"))
+            }
+            (_, counter) => counter.unwrap_or_default(),
+        };
+        let mut params = Self::try_new(kind, secret, algorithm, digits, period, counter)?;
+        params.issuer = issuer;
+        params.account = account;
+        Ok(params)
+    }
+
+    /// Build the `otpauth://` URI understood by authenticator apps.
+    pub fn to_uri(&self) -> String {
+        let kind = match self.kind {
+            OtpType::Totp => "totp",
+            OtpType::Hotp => "hotp",
+        };
+        let account = self.account.as_deref().unwrap_or_default();
+        let label = match &self.issuer {
+            Some(issuer) => format!("{}:{}", percent_encode(issuer), percent_encode(account)),
+            None => percent_encode(account),
+        };
+        let mut uri = format!(
+            "otpauth://{kind}/{label}?secret={}",
+            BASE32_NOPAD.encode(&self.secret)
+        );
+        if let Some(issuer) = &self.issuer {
+            uri.push_str(&format!("&issuer={}", percent_encode(issuer)));
+        }
+        let algorithm = match self.algorithm {
+            OtpAlgorithm::Sha1 => "SHA1",
+            OtpAlgorithm::Sha256 => "SHA256",
+            OtpAlgorithm::Sha512 => "SHA512",
+        };
+        uri.push_str(&format!("&algorithm={algorithm}&digits={}", self.digits));
+        match self.kind {
+            OtpType::Totp => uri.push_str(&format!("&period={}", self.period)),
+            OtpType::Hotp => uri.push_str(&format!("&counter={}", self.counter)),
+        }
+        uri
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        if self.secret.is_empty() {
+            return Err(anyhow!("otp secret cannot be empty"));
+        }
+        if !(6..=8).contains(&self.digits) {
+            return Err(anyhow!("otp digits must be between 6 and 8"));
+        }
+        if self.period == 0 {
+            return Err(anyhow!("totp period cannot be 0"));
+        }
+        Ok(())
+    }
+}
+
+/// Generate the code at `time` (unix seconds) for TOTP, or at the counter for HOTP.
+pub fn process_otp_generate(params: &OtpParams, time: Option<u64>) -> anyhow::Result<String> {
+    let counter = match params.kind {
+        OtpType::Totp => get_time(time)? / params.period,
+        OtpType::Hotp => params.counter,
+    };
+    hotp(params, counter)
+}
+
+/// Verify the code, accepting `window` steps (TOTP) or counters (HOTP) around the current one.
+/// Return the offset of the step matched.
+pub fn process_otp_verify(
+    params: &OtpParams,
+    code: &str,
+    time: Option<u64>,
+    window: u64,
+) -> anyhow::Result<Option<i64>> {
+    let current = match params.kind {
+        OtpType::Totp => get_time(time)? / params.period,
+        OtpType::Hotp => params.counter,
+    };
+    // HOTP counters only move forward, so only look ahead for them.
+    let start = match params.kind {
+        OtpType::Totp => current.saturating_sub(window),
+        OtpType::Hotp => current,
+    };
+    for counter in start..=current.saturating_add(window) {
+        if constant_time_eq(hotp(params, counter)?.as_bytes(), code.as_bytes()) {
+            let offset = match counter.checked_sub(current) {
+                Some(ahead) => i64::try_from(ahead),
+                None => i64::try_from(current - counter).map(|behind| -behind),
+            };
+            return Ok(Some(
+                offset.map_err(|_| anyhow!("otp window is too large"))?,
+            ));
+        }
+    }
+    Ok(None)
+}
+
+/// Generate a random secret of `len` bytes.
+pub fn process_otp_new_secret(len: usize) -> Vec<u8> {
+    let mut secret = vec![0u8; len];
+    utils::rng().fill_bytes(&mut secret);
+    secret
+}
+
+/// Decode a base32 secret, ignoring case, spaces and padding.
+pub fn decode_secret(secret: &str) -> anyhow::Result<Vec<u8>> {
+    let secret: String = secret
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '=' && *c != '-')
+        .collect();
+    BASE32_NOPAD
+        .decode(secret.to_uppercase().as_bytes())
+        .map_err(|e| anyhow!("invalid base32 secret: {}", e))
+}
+
+/// RFC 4226 HOTP with the dynamic truncation.
+fn hotp(params: &OtpParams, counter: u64) -> anyhow::Result<String> {
+    let msg = counter.to_be_bytes();
+    let hash = match params.algorithm {
+        OtpAlgorithm::Sha1 => hmac::<Hmac<Sha1>>(&params.secret, &msg)?,
+        OtpAlgorithm::Sha256 => hmac::<Hmac<Sha256>>(&params.secret, &msg)?,
+        OtpAlgorithm::Sha512 => hmac::<Hmac<Sha512>>(&params.secret, &msg)?,
+    };
+    let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes(hash[offset..offset + 4].try_into()?) & 0x7fff_ffff;
+    let code = binary % 10u32.pow(params.digits as u32);
+    Ok(format!("{:0width$}", code, width = params.digits as usize))
+}
+
+fn hmac<M: Mac + KeyInit>(key: &[u8], msg: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut mac = <M as KeyInit>::new_from_slice(key)?;
+    mac.update(msg);
+    Ok(mac.finalize().into_bytes().to_vec())
+}
+
+fn get_time(time: Option<u64>) -> anyhow::Result<u64> {
+    match time {
+        Some(time) => Ok(time),
+        None => Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs()),
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn percent_encode(s: &str) -> String {
+    utf8_percent_encode(s, URI_COMPONENT).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(kind: OtpType, secret: &[u8], algorithm: OtpAlgorithm, digits: u8) -> OtpParams {
+        OtpParams::try_new(kind, secret.to_vec(), algorithm, digits, 30, 0).unwrap()
+    }
+
+    #[test]
+    fn test_hotp_rfc4226() -> anyhow::Result<()> {
+        let mut p = params(
+            OtpType::Hotp,
+            b"12345678901234567890",
+            OtpAlgorithm::Sha1,
+            6,
+        );
+        let expected = ["755224", "287082", "359152", "969429", "338314"];
+        for (counter, code) in expected.iter().enumerate() {
+            p.counter = counter as u64;
+            assert_eq!(process_otp_generate(&p, None)?, *code);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_totp_rfc6238() -> anyhow::Result<()> {
+        let sha1 = params(
+            OtpType::Totp,
+            b"12345678901234567890",
+            OtpAlgorithm::Sha1,
+            8,
+        );
+        let sha256 = params(
+            OtpType::Totp,
+            b"12345678901234567890123456789012",
+            OtpAlgorithm::Sha256,
+            8,
+        );
+        let sha512 = params(
+            OtpType::Totp,
+            b"1234567890123456789012345678901234567890123456789012345678901234",
+            OtpAlgorithm::Sha512,
+            8,
+        );
+        assert_eq!(process_otp_generate(&sha1, Some(59))?, "94287082");
+        assert_eq!(process_otp_generate(&sha256, Some(59))?, "46119246");
+        assert_eq!(process_otp_generate(&sha512, Some(59))?, "90693936");
+        assert_eq!(process_otp_generate(&sha1, Some(1111111109))?, "07081804");
+        assert_eq!(process_otp_generate(&sha256, Some(1234567890))?, "91819424");
+        assert_eq!(
+            process_otp_generate(&sha512, Some(20000000000))?,
+            "47863826"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_otp_verify() -> anyhow::Result<()> {
+        let p = params(
+            OtpType::Totp,
+            b"12345678901234567890",
+            OtpAlgorithm::Sha1,
+            8,
+        );
+        assert_eq!(process_otp_verify(&p, "94287082", Some(59), 0)?, Some(0));
+        assert_eq!(process_otp_verify(&p, "94287082", Some(89), 1)?, Some(-1));
+        assert_eq!(process_otp_verify(&p, "94287082", Some(89), 0)?, None);
+        assert_eq!(process_otp_verify(&p, "00000000", Some(59), 1)?, None);
+
+        let mut p = params(
+            OtpType::Hotp,
+            b"12345678901234567890",
+            OtpAlgorithm::Sha1,
+            6,
+        );
+        p.counter = u64::MAX - 1;
+        assert_eq!(process_otp_verify(&p, "000000", None, 10)?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_otp_uri() -> anyhow::Result<()> {
+        let uri = "otpauth://totp/ACME%20Co:john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&issuer=ACME%20Co&algorithm=SHA256&digits=7&period=60";
+        let p = OtpParams::from_uri(uri)?;
+        assert_eq!(p.kind, OtpType::Totp);
+        assert_eq!(p.issuer.as_deref(), Some("ACME Co"));
+        assert_eq!(p.account.as_deref(), Some("john.doe@email.com"));
+        assert_eq!(p.algorithm, OtpAlgorithm::Sha256);
+        assert_eq!(p.digits, 7);
+        assert_eq!(p.period, 60);
+        assert_eq!(OtpParams::from_uri(&p.to_uri())?, p);
+
+        assert!(OtpParams::from_uri("otpauth://hotp/x?secret=GEZDGNBV").is_err());
+        assert!(OtpParams::from_uri("https://totp/x?secret=GEZDGNBV").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_secret() -> anyhow::Result<()> {
+        assert_eq!(decode_secret("gezd gnbv")?, b"12345");
+        assert_eq!(decode_secret("GEZDGNBV====")?, b"12345");
+        assert!(decode_secret("1").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,128 @@
+use anyhow::anyhow;
+use serde::Serialize;
+use zxcvbn::{matching::patterns::MatchPattern, zxcvbn, Match};
+
+use super::gen_pass::{charset_classes, CrackTime};
+
+/// The strength analysis of an existing password.
+#[derive(Debug, Serialize)]
+pub struct PasswordAnalysis {
+    pub length: usize,
+    pub classes: Vec<&'static str>,
+    pub score: u8,
+    pub guesses: u64,
+    pub guesses_log10: f64,
+    pub crack_time: CrackTime,
+    pub patterns: Vec<PatternMatch>,
+    pub warning: Option<String>,
+    pub suggestions: Vec<String>,
+}
+
+/// A part of the password matched by one of the zxcvbn patterns.
+#[derive(Debug, Serialize)]
+pub struct PatternMatch {
+    pub pattern: &'static str,
+    pub token: String,
+    pub detail: String,
+}
+
+pub fn process_pass_check(
+    password: &str,
+    user_inputs: &[&str],
+) -> anyhow::Result<PasswordAnalysis> {
+    let estimate = zxcvbn(password, user_inputs).map_err(|e| anyhow!("{}", e))?;
+    let (warning, suggestions) = match estimate.feedback() {
+        Some(feedback) => (
+            feedback.warning().map(|w| w.to_string()),
+            feedback
+                .suggestions()
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+        ),
+        None => (None, vec![]),
+    };
+    Ok(PasswordAnalysis {
+        length: password.chars().count(),
+        classes: charset_classes(password),
+        score: estimate.score(),
+        guesses: estimate.guesses(),
+        guesses_log10: (estimate.guesses_log10() * 100.0).round() / 100.0,
+        crack_time: CrackTime::new(estimate.crack_times()),
+        patterns: estimate.sequence().iter().map(PatternMatch::from).collect(),
+        warning,
+        suggestions,
+    })
+}
+
+impl From<&Match> for PatternMatch {
+    fn from(m: &Match) -> Self {
+        let (pattern, detail) = match &m.pattern {
+            MatchPattern::Dictionary(p) => {
+                let mut detail = format!(
+                    "`{}` in {:?} dictionary, rank {}",
+                    p.matched_word, p.dictionary_name, p.rank
+                );
+                if p.reversed {
+                    detail.push_str(", reversed");
+                }
+                if let Some(sub) = &p.sub_display {
+                    detail.push_str(&format!(", l33t {sub}"));
+                }
+                ("dictionary", detail)
+            }
+            MatchPattern::Spatial(p) => (
+                "spatial",
+                format!(
+                    "{} keyboard, {} turn(s), {} shifted",
+                    p.graph, p.turns, p.shifted_count
+                ),
+            ),
+            MatchPattern::Repeat(p) => (
+                "repeat",
+                format!("`{}` repeated {} times", p.base_token, p.repeat_count),
+            ),
+            MatchPattern::Sequence(p) => (
+                "sequence",
+                format!(
+                    "{} {}",
+                    match p.ascending {
+                        true => "ascending",
+                        false => "descending",
+                    },
+                    p.sequence_name
+                ),
+            ),
+            MatchPattern::Regex(p) => ("regex", p.regex_name.to_string()),
+            MatchPattern::Date(p) => ("date", format!("{:04}-{:02}-{:02}", p.year, p.month, p.day)),
+            MatchPattern::BruteForce => ("bruteforce", String::new()),
+        };
+        Self {
+            pattern,
+            token: m.token.clone(),
+            detail,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_process_pass_check() -> anyhow::Result<()> {
+        let analysis = process_pass_check("password1990", &[])?;
+        assert_eq!(analysis.length, 12);
+        assert!(analysis.score <= 1);
+        assert!(analysis.patterns.iter().any(|p| p.pattern == "dictionary"));
+
+        let analysis = process_pass_check("abcdef", &[])?;
+        assert_eq!(analysis.patterns[0].pattern, "sequence");
+
+        let analysis = process_pass_check("acme2024", &["acme"])?;
+        assert!(analysis.patterns[0].detail.contains("UserInputs"));
+
+        assert!(process_pass_check("", &[]).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,117 @@
+use anyhow::anyhow;
+use argon2::{
+    password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Algorithm, Argon2, Params, Version,
+};
+use rand::RngCore;
+use scrypt::Scrypt;
+
+use crate::utils;
+
+/// bcrypt only uses the first 72 bytes of the password.
+const BCRYPT_MAX_LENGTH: usize = 72;
+
+/// Hash the password with Argon2id into a PHC string.
+/// `memory` is in KiB, `iterations` and `parallelism` are the time and lanes costs.
+pub fn process_hash_argon2(
+    password: &str,
+    memory: u32,
+    iterations: u32,
+    parallelism: u32,
+) -> anyhow::Result<String> {
+    let params = Params::new(memory, iterations, parallelism, None)
+        .map_err(|e| anyhow!("invalid argon2 params: {}", e))?;
+    let salt = SaltString::generate(&mut utils::rng());
+    let hash = Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(|e| anyhow!("hash failed, {}", e))?;
+    Ok(hash.to_string())
+}
+
+/// Hash the password with scrypt into a PHC string, the CPU/memory cost is `2^log_n`.
+pub fn process_hash_scrypt(password: &str, log_n: u8, r: u32, p: u32) -> anyhow::Result<String> {
+    let params = scrypt::Params::new(log_n, r, p, scrypt::Params::RECOMMENDED_LEN)
+        .map_err(|e| anyhow!("invalid scrypt params: {}", e))?;
+    let salt = SaltString::generate(&mut utils::rng());
+    let hash = Scrypt
+        .hash_password_customized(password.as_bytes(), None, None, params, &salt)
+        .map_err(|e| anyhow!("hash failed, {}", e))?;
+    Ok(hash.to_string())
+}
+
+/// Hash the password with bcrypt into the `$2b$` modular crypt format,
+/// bcrypt has no PHC representation.
+pub fn process_hash_bcrypt(password: &str, cost: u32) -> anyhow::Result<String> {
+    if password.len() > BCRYPT_MAX_LENGTH {
+        return Err(anyhow!(
+            "bcrypt only supports passwords up to {BCRYPT_MAX_LENGTH} bytes"
+        ));
+    }
+    let mut salt = [0u8; 16];
+    utils::rng().fill_bytes(&mut salt);
+    Ok(bcrypt::hash_with_salt(password, cost, salt)?.format_for_version(bcrypt::Version::TwoB))
+}
+
+/// Verify the password against a hash, the algorithm is detected from the hash.
+pub fn process_passwd_verify(password: &str, hash: &str) -> anyhow::Result<bool> {
+    let hash = hash.trim();
+    if ["$2a$", "$2b$", "$2x$", "$2y$"]
+        .iter()
+        .any(|prefix| hash.starts_with(prefix))
+    {
+        return Ok(bcrypt::verify(password, hash)?);
+    }
+
+    let parsed = PasswordHash::new(hash).map_err(|e| anyhow!("invalid PHC string: {}", e))?;
+    let res = match parsed.algorithm.as_str() {
+        "argon2id" | "argon2i" | "argon2d" => {
+            Argon2::default().verify_password(password.as_bytes(), &parsed)
+        }
+        "scrypt" => Scrypt.verify_password(password.as_bytes(), &parsed),
+        algorithm => return Err(anyhow!("unsupported hash algorithm: {}", algorithm)),
+    };
+    match res {
+        Ok(()) => Ok(true),
+        Err(argon2::password_hash::Error::Password) => Ok(false),
+        Err(e) => Err(anyhow!("verify failed, {}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_argon2_hash_verify() -> anyhow::Result<()> {
+        let hash = process_hash_argon2("hunter2", 1024, 1, 1)?;
+        assert!(hash.starts_with("$argon2id$v=19$m=1024,t=1,p=1$"));
+        assert!(process_passwd_verify("hunter2", &hash)?);
+        assert!(!process_passwd_verify("hunter3", &hash)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_scrypt_hash_verify() -> anyhow::Result<()> {
+        let hash = process_hash_scrypt("hunter2", 4, 8, 1)?;
+        assert!(hash.starts_with("$scrypt$ln=4,r=8,p=1$"));
+        assert!(process_passwd_verify("hunter2", &hash)?);
+        assert!(!process_passwd_verify("hunter3", &hash)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_bcrypt_hash_verify() -> anyhow::Result<()> {
+        let hash = process_hash_bcrypt("hunter2", 4)?;
+        assert!(hash.starts_with("$2b$04$"));
+        assert!(process_passwd_verify("hunter2", &hash)?);
+        assert!(!process_passwd_verify("hunter3", &hash)?);
+        assert!(process_hash_bcrypt(&"x".repeat(73), 4).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_passwd_verify_invalid_hash() {
+        assert!(process_passwd_verify("hunter2", "not a hash").is_err());
+        assert!(process_passwd_verify("hunter2", "$pbkdf2$i=1$c2FsdA$aGFzaA").is_err());
+    }
+}
@@ -0,0 +1,578 @@
+use std::{collections::HashMap, fmt::Write, path::Path};
+
+use anyhow::anyhow;
+use protobuf::descriptor::{
+    field_descriptor_proto::Type, DescriptorProto, EnumDescriptorProto, FieldDescriptorProto,
+    FileDescriptorProto,
+};
+
+use super::codec::get_codec_input;
+use crate::cli::Codec;
+
+/// Messages and groups nested deeper than this aren't decoded, like protobuf's own limit.
+const MAX_DEPTH: usize = 100;
+
+/// A field as it's encoded on the wire.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WireField {
+    pub number: u32,
+    pub value: WireValue,
+}
+
+/// The value of a field for each wire type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WireValue {
+    Varint(u64),
+    I64(u64),
+    Len(Vec<u8>),
+    Group(Vec<WireField>),
+    I32(u32),
+}
+
+/// The messages and enums of `.proto` files, by fully qualified name like `.pkg.Message`.
+#[derive(Debug, Default)]
+pub struct ProtoSchema {
+    messages: HashMap<String, DescriptorProto>,
+    enums: HashMap<String, EnumDescriptorProto>,
+    /// The first message of the schema, decoded when no message is named.
+    first: Option<String>,
+}
+
+/// Decode protobuf bytes into a tree of fields. Without a schema the wire types are shown and
+/// length-delimited fields are guessed as strings, nested messages, packed varints or bytes.
+/// With a schema, `message` names the root message, by default the first one of the file.
+pub fn process_proto_decode(
+    input: &str,
+    codec: Option<Codec>,
+    proto: Option<&str>,
+    message: Option<&str>,
+) -> anyhow::Result<String> {
+    proto_decode(&get_codec_input(input, codec)?, proto, message)
+}
+
+pub fn proto_decode(
+    data: &[u8],
+    proto: Option<&str>,
+    message: Option<&str>,
+) -> anyhow::Result<String> {
+    let fields = parse_fields(data)?;
+    let schema = proto.map(ProtoSchema::load).transpose()?;
+    let root = match &schema {
+        Some(schema) => Some(schema.message(message)?),
+        None => None,
+    };
+    let mut res = String::new();
+    render_fields(&mut res, &fields, schema.as_ref(), root, 0)?;
+    Ok(res)
+}
+
+/// Parse a whole message, the data must end on a field boundary.
+pub fn parse_fields(data: &[u8]) -> anyhow::Result<Vec<WireField>> {
+    parse_message(data, 0)
+}
+
+/// Parse a message nested `depth` levels deep.
+fn parse_message(data: &[u8], depth: usize) -> anyhow::Result<Vec<WireField>> {
+    parse_until(data, &mut 0, None, depth)
+}
+
+impl ProtoSchema {
+    /// Parse a `.proto` file, its imports are looked up next to it.
+    pub fn load(path: &str) -> anyhow::Result<Self> {
+        let path = Path::new(path);
+        let dir = path.parent().unwrap_or(Path::new(""));
+        let parsed = protobuf_parse::Parser::new()
+            .pure()
+            .include(match dir.as_os_str().is_empty() {
+                true => Path::new("."),
+                false => dir,
+            })
+            .input(path)
+            .parse_and_typecheck()?;
+        let input = parsed.relative_paths.first().map(|path| path.to_string());
+        let mut schema = Self::default();
+        for file in &parsed.file_descriptors {
+            schema.add_file(file);
+            if Some(file.name()) == input.as_deref() {
+                schema.first = file
+                    .message_type
+                    .first()
+                    .map(|msg| qualify(file.package(), msg.name()));
+            }
+        }
+        Ok(schema)
+    }
+
+    /// Look up a message by name, with or without the package and leading dot.
+    pub fn message(&self, name: Option<&str>) -> anyhow::Result<&DescriptorProto> {
+        let name = match name {
+            Some(name) => name.trim_start_matches('.'),
+            None => {
+                let first = self
+                    .first
+                    .as_deref()
+                    .ok_or_else(|| anyhow!("no message in the .proto file"))?;
+                return Ok(&self.messages[first]);
+            }
+        };
+        let suffix = format!(".{name}");
+        if let Some(msg) = self.messages.get(&suffix) {
+            return Ok(msg);
+        }
+        let mut found = self.messages.iter().filter(|(k, _)| k.ends_with(&suffix));
+        match (found.next(), found.next()) {
+            (Some((_, msg)), None) => Ok(msg),
+            (Some(_), Some(_)) => Err(anyhow!("message `{}` is ambiguous", name)),
+            _ => Err(anyhow!("message `{}` not found", name)),
+        }
+    }
+
+    fn add_file(&mut self, file: &FileDescriptorProto) {
+        let prefix = match file.package() {
+            "" => String::new(),
+            package => format!(".{package}"),
+        };
+        for msg in &file.message_type {
+            self.add_message(&prefix, msg);
+        }
+        for e in &file.enum_type {
+            self.enums
+                .insert(format!("{prefix}.{}", e.name()), e.clone());
+        }
+    }
+
+    fn add_message(&mut self, prefix: &str, msg: &DescriptorProto) {
+        let name = format!("{prefix}.{}", msg.name());
+        for nested in &msg.nested_type {
+            self.add_message(&name, nested);
+        }
+        for e in &msg.enum_type {
+            self.enums.insert(format!("{name}.{}", e.name()), e.clone());
+        }
+        self.messages.insert(name, msg.clone());
+    }
+}
+
+fn qualify(package: &str, name: &str) -> String {
+    match package {
+        "" => format!(".{name}"),
+        package => format!(".{package}.{name}"),
+    }
+}
+
+fn parse_until(
+    data: &[u8],
+    pos: &mut usize,
+    group: Option<u32>,
+    depth: usize,
+) -> anyhow::Result<Vec<WireField>> {
+    if depth > MAX_DEPTH {
+        return Err(anyhow!(
+            "messages are nested deeper than {} levels",
+            MAX_DEPTH
+        ));
+    }
+    let mut fields = Vec::new();
+    while *pos < data.len() {
+        let start = *pos;
+        let tag = read_varint(data, pos)?;
+        let number = u32::try_from(tag >> 3)
+            .ok()
+            .filter(|n| (1..1 << 29).contains(n))
+            .ok_or_else(|| anyhow!("invalid field number at byte {}", start))?;
+        let value = match tag & 7 {
+            0 => WireValue::Varint(read_varint(data, pos)?),
+            1 => WireValue::I64(u64::from_le_bytes(read_bytes(data, pos, 8)?.try_into()?)),
+            2 => {
+                let len = usize::try_from(read_varint(data, pos)?)?;
+                WireValue::Len(read_bytes(data, pos, len)?.to_vec())
+            }
+            3 => WireValue::Group(parse_until(data, pos, Some(number), depth + 1)?),
+            4 if group == Some(number) => return Ok(fields),
+            4 => return Err(anyhow!("unexpected end group at byte {}", start)),
+            5 => WireValue::I32(u32::from_le_bytes(read_bytes(data, pos, 4)?.try_into()?)),
+            wire => return Err(anyhow!("invalid wire type {} at byte {}", wire, start)),
+        };
+        fields.push(WireField { number, value });
+    }
+    match group {
+        Some(number) => Err(anyhow!("group {} is not terminated", number)),
+        None => Ok(fields),
+    }
+}
+
+fn read_varint(data: &[u8], pos: &mut usize) -> anyhow::Result<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *data
+            .get(*pos)
+            .ok_or_else(|| anyhow!("truncated varint at byte {}", *pos))?;
+        *pos += 1;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte < 0x80 {
+            return Ok(value);
+        }
+    }
+    Err(anyhow!("varint is too long at byte {}", *pos))
+}
+
+fn read_bytes<'a>(data: &'a [u8], pos: &mut usize, len: usize) -> anyhow::Result<&'a [u8]> {
+    let end = pos
+        .checked_add(len)
+        .filter(|end| *end <= data.len())
+        .ok_or_else(|| anyhow!("truncated field at byte {}", *pos))?;
+    let bytes = &data[*pos..end];
+    *pos = end;
+    Ok(bytes)
+}
+
+fn render_fields(
+    out: &mut String,
+    fields: &[WireField],
+    schema: Option<&ProtoSchema>,
+    msg: Option<&DescriptorProto>,
+    depth: usize,
+) -> anyhow::Result<()> {
+    let indent = "  ".repeat(depth);
+    for field in fields {
+        let desc = msg.and_then(|msg| msg.field.iter().find(|f| f.number() == field.number as i32));
+        let label = match desc {
+            Some(desc) => format!("{} {}", field.number, desc.name()),
+            None => field.number.to_string(),
+        };
+        let typed = match (schema, desc) {
+            (Some(schema), Some(desc)) => {
+                render_typed(out, &field.value, schema, desc, &indent, &label, depth)?
+            }
+            _ => false,
+        };
+        if !typed {
+            render_raw(out, &field.value, &indent, &label, depth)?;
+        }
+    }
+    Ok(())
+}
+
+/// Render a field with its declared type, `false` if the wire type doesn't match it.
+fn render_typed(
+    out: &mut String,
+    value: &WireValue,
+    schema: &ProtoSchema,
+    desc: &FieldDescriptorProto,
+    indent: &str,
+    label: &str,
+    depth: usize,
+) -> anyhow::Result<bool> {
+    let ty = type_name(desc);
+    match (desc.type_(), value) {
+        (Type::TYPE_MESSAGE, WireValue::Len(bytes)) => {
+            let Ok(fields) = parse_message(bytes, depth + 1) else {
+                return Ok(false);
+            };
+            writeln!(out, "{indent}{label} <{ty}> {{")?;
+            render_fields(
+                out,
+                &fields,
+                Some(schema),
+                schema.messages.get(desc.type_name()),
+                depth + 1,
+            )?;
+            writeln!(out, "{indent}}}")?;
+        }
+        (Type::TYPE_GROUP, WireValue::Group(fields)) => {
+            writeln!(out, "{indent}{label} <{ty}> {{")?;
+            render_fields(
+                out,
+                fields,
+                Some(schema),
+                schema.messages.get(desc.type_name()),
+                depth + 1,
+            )?;
+            writeln!(out, "{indent}}}")?;
+        }
+        (Type::TYPE_STRING, WireValue::Len(bytes)) => {
+            let s = String::from_utf8_lossy(bytes);
+            writeln!(
+                out,
+                "{indent}{label} <{ty}>: {}",
+                serde_json::to_string(&s)?
+            )?;
+        }
+        (Type::TYPE_BYTES, WireValue::Len(bytes)) => {
+            writeln!(out, "{indent}{label} <{ty}>: {}", render_bytes(bytes))?;
+        }
+        (_, WireValue::Len(bytes)) => {
+            let Some(values) = parse_packed(bytes, desc.type_(), schema, desc) else {
+                return Ok(false);
+            };
+            writeln!(
+                out,
+                "{indent}{label} <packed {ty}>: [{}]",
+                values.join(", ")
+            )?;
+        }
+        (ty_, value) => match render_scalar(value, ty_, schema, desc) {
+            Some(s) => writeln!(out, "{indent}{label} <{ty}>: {s}")?,
+            None => return Ok(false),
+        },
+    }
+    Ok(true)
+}
+
+/// Render a field from its wire type alone.
+fn render_raw(
+    out: &mut String,
+    value: &WireValue,
+    indent: &str,
+    label: &str,
+    depth: usize,
+) -> anyhow::Result<()> {
+    match value {
+        WireValue::Varint(v) => match *v > i64::MAX as u64 {
+            true => writeln!(out, "{indent}{label} <varint>: {v} (int64 {})", *v as i64)?,
+            false => writeln!(out, "{indent}{label} <varint>: {v}")?,
+        },
+        WireValue::I64(v) => writeln!(
+            out,
+            "{indent}{label} <i64>: {v} (double {})",
+            f64::from_bits(*v)
+        )?,
+        WireValue::I32(v) => writeln!(
+            out,
+            "{indent}{label} <i32>: {v} (float {})",
+            f32::from_bits(*v)
+        )?,
+        WireValue::Group(fields) => {
+            writeln!(out, "{indent}{label} <group> {{")?;
+            render_fields(out, fields, None, None, depth + 1)?;
+            writeln!(out, "{indent}}}")?;
+        }
+        WireValue::Len(bytes) => match guess_len(bytes, depth + 1) {
+            LenGuess::String(s) => {
+                writeln!(out, "{indent}{label} <len>: {}", serde_json::to_string(s)?)?
+            }
+            LenGuess::Message(fields) => {
+                writeln!(out, "{indent}{label} <len> {{")?;
+                render_fields(out, &fields, None, None, depth + 1)?;
+                writeln!(out, "{indent}}}")?;
+            }
+            LenGuess::Packed(values) => {
+                let values: Vec<_> = values.iter().map(u64::to_string).collect();
+                writeln!(
+                    out,
+                    "{indent}{label} <packed varint>: [{}]",
+                    values.join(", ")
+                )?;
+            }
+            LenGuess::Bytes => writeln!(out, "{indent}{label} <len>: {}", render_bytes(bytes))?,
+        },
+    }
+    Ok(())
+}
+
+enum LenGuess<'a> {
+    String(&'a str),
+    Message(Vec<WireField>),
+    Packed(Vec<u64>),
+    Bytes,
+}
+
+/// Guess what a length-delimited field `depth` levels deep holds: printable UTF-8 is a
+/// string, then a valid message if it isn't too deep, then at least two varints,
+/// otherwise bytes.
+fn guess_len(bytes: &[u8], depth: usize) -> LenGuess<'_> {
+    if let Ok(s) = std::str::from_utf8(bytes) {
+        if !s.chars().any(|c| c.is_control() && !c.is_whitespace()) {
+            return LenGuess::String(s);
+        }
+    }
+    if let Ok(fields) = parse_message(bytes, depth) {
+        return LenGuess::Message(fields);
+    }
+    let mut pos = 0;
+    let mut values = Vec::new();
+    while pos < bytes.len() {
+        match read_varint(bytes, &mut pos) {
+            Ok(v) => values.push(v),
+            Err(_) => return LenGuess::Bytes,
+        }
+    }
+    match values.len() >= 2 {
+        true => LenGuess::Packed(values),
+        false => LenGuess::Bytes,
+    }
+}
+
+fn parse_packed(
+    bytes: &[u8],
+    ty: Type,
+    schema: &ProtoSchema,
+    desc: &FieldDescriptorProto,
+) -> Option<Vec<String>> {
+    let mut pos = 0;
+    let mut values = Vec::new();
+    while pos < bytes.len() {
+        let value = match ty {
+            Type::TYPE_DOUBLE | Type::TYPE_FIXED64 | Type::TYPE_SFIXED64 => WireValue::I64(
+                u64::from_le_bytes(read_bytes(bytes, &mut pos, 8).ok()?.try_into().ok()?),
+            ),
+            Type::TYPE_FLOAT | Type::TYPE_FIXED32 | Type::TYPE_SFIXED32 => WireValue::I32(
+                u32::from_le_bytes(read_bytes(bytes, &mut pos, 4).ok()?.try_into().ok()?),
+            ),
+            _ => WireValue::Varint(read_varint(bytes, &mut pos).ok()?),
+        };
+        values.push(render_scalar(&value, ty, schema, desc)?);
+    }
+    Some(values)
+}
+
+/// Render a number with its declared type, `None` if the wire type doesn't match it.
+fn render_scalar(
+    value: &WireValue,
+    ty: Type,
+    schema: &ProtoSchema,
+    desc: &FieldDescriptorProto,
+) -> Option<String> {
+    let s = match (ty, value) {
+        (Type::TYPE_INT32, WireValue::Varint(v)) => (*v as i32).to_string(),
+        (Type::TYPE_INT64, WireValue::Varint(v)) => (*v as i64).to_string(),
+        (Type::TYPE_UINT32, WireValue::Varint(v)) => (*v as u32).to_string(),
+        (Type::TYPE_UINT64, WireValue::Varint(v)) => v.to_string(),
+        (Type::TYPE_SINT32 | Type::TYPE_SINT64, WireValue::Varint(v)) => {
+            ((*v >> 1) as i64 ^ -((*v & 1) as i64)).to_string()
+        }
+        (Type::TYPE_BOOL, WireValue::Varint(v)) => (*v != 0).to_string(),
+        (Type::TYPE_ENUM, WireValue::Varint(v)) => {
+            let number = *v as i32;
+            schema
+                .enums
+                .get(desc.type_name())
+                .and_then(|e| e.value.iter().find(|value| value.number() == number))
+                .map_or_else(|| number.to_string(), |value| value.name().to_string())
+        }
+        (Type::TYPE_FIXED64, WireValue::I64(v)) => v.to_string(),
+        (Type::TYPE_SFIXED64, WireValue::I64(v)) => (*v as i64).to_string(),
+        (Type::TYPE_DOUBLE, WireValue::I64(v)) => f64::from_bits(*v).to_string(),
+        (Type::TYPE_FIXED32, WireValue::I32(v)) => v.to_string(),
+        (Type::TYPE_SFIXED32, WireValue::I32(v)) => (*v as i32).to_string(),
+        (Type::TYPE_FLOAT, WireValue::I32(v)) => f32::from_bits(*v).to_string(),
+        _ => return None,
+    };
+    Some(s)
+}
+
+fn type_name(desc: &FieldDescriptorProto) -> String {
+    match desc.type_() {
+        Type::TYPE_MESSAGE | Type::TYPE_ENUM | Type::TYPE_GROUP => desc
+            .type_name()
+            .rsplit('.')
+            .next()
+            .unwrap_or_default()
+            .to_string(),
+        ty => format!("{ty:?}").trim_start_matches("TYPE_").to_lowercase(),
+    }
+}
+
+fn render_bytes(bytes: &[u8]) -> String {
+    format!("h'{}'", data_encoding::HEXLOWER.encode(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USER: &str =
+        "089601 1203616e61 1a03038e02 22060a044f736c6f 2801 3003 390000000000000440 4202ff00";
+
+    fn user() -> Vec<u8> {
+        data_encoding::HEXLOWER
+            .decode(USER.replace(' ', "").as_bytes())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_parse_fields() -> anyhow::Result<()> {
+        let fields = parse_fields(&user())?;
+        assert_eq!(fields.len(), 8);
+        assert_eq!(
+            fields[0],
+            WireField {
+                number: 1,
+                value: WireValue::Varint(150)
+            }
+        );
+        // group 1 { 2: 5 }
+        let group = parse_fields(&[0x0b, 0x10, 0x05, 0x0c])?;
+        assert_eq!(
+            group[0].value,
+            WireValue::Group(vec![WireField {
+                number: 2,
+                value: WireValue::Varint(5)
+            }])
+        );
+        assert!(parse_fields(&[0x08]).is_err());
+        assert!(parse_fields(&[0x12, 0x05, 0x61]).is_err());
+        assert!(parse_fields(&[0x0f]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_max_depth() -> anyhow::Result<()> {
+        assert!(parse_fields(&[0x0b; 200 * 1024]).is_err());
+
+        // field 1 holding field 1 holding ... 20k levels deep
+        let mut data = Vec::new();
+        for _ in 0..20_000 {
+            let mut outer = vec![0x0a];
+            let mut len = data.len();
+            while len >= 0x80 {
+                outer.push(len as u8 | 0x80);
+                len >>= 7;
+            }
+            outer.push(len as u8);
+            outer.extend_from_slice(&data);
+            data = outer;
+        }
+        let res = proto_decode(&data, None, None)?;
+        assert_eq!(res.matches('{').count(), MAX_DEPTH);
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_raw() -> anyhow::Result<()> {
+        assert_eq!(
+            proto_decode(&user(), None, None)?,
+            "1 <varint>: 150\n\
+             2 <len>: \"ana\"\n\
+             3 <packed varint>: [3, 270]\n\
+             4 <len> {\n  1 <len>: \"Oslo\"\n}\n\
+             5 <varint>: 1\n\
+             6 <varint>: 3\n\
+             7 <i64>: 4612811918334230528 (double 2.5)\n\
+             8 <len>: h'ff00'\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_schema() -> anyhow::Result<()> {
+        let expected = "1 id <int64>: 150\n\
+                        2 name <string>: \"ana\"\n\
+                        3 scores <packed int32>: [3, 270]\n\
+                        4 address <Address> {\n  1 city <string>: \"Oslo\"\n}\n\
+                        5 role <Role>: ADMIN\n\
+                        6 delta <sint32>: -2\n\
+                        7 balance <double>: 2.5\n\
+                        8 avatar <bytes>: h'ff00'\n";
+        assert_eq!(
+            proto_decode(&user(), Some("fixtures/user.proto"), None)?,
+            expected
+        );
+        assert_eq!(
+            proto_decode(&user(), Some("fixtures/user.proto"), Some("demo.User"))?,
+            expected
+        );
+        assert!(proto_decode(&user(), Some("fixtures/user.proto"), Some("Missing")).is_err());
+        Ok(())
+    }
+}
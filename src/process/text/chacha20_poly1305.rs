@@ -1,9 +1,9 @@
 use std::io::Read;
 
-use crate::process::get_content;
+use crate::{process::get_content, utils};
 use anyhow::anyhow;
 use chacha20poly1305::{
-    aead::{Aead, AeadCore, KeyInit, OsRng},
+    aead::{Aead, AeadCore, KeyInit},
     ChaCha20Poly1305, Key, Nonce,
 };
 
@@ -25,7 +25,7 @@ pub struct Chacha20Poly1305Decryptor {
 
 impl Encrypt for Chacha20Poly1305Encryptor {
     fn encrypt(&self, reader: &mut dyn Read) -> anyhow::Result<Vec<u8>> {
-        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng); // 96-bits; unique per message
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut utils::rng()); // 96-bits; unique per message
         let buf = get_content(reader)?;
         let res = self
             .cipher
@@ -91,7 +91,7 @@ impl Decrypt for Chacha20Poly1305Decryptor {
 
 fn get_key(key: &[u8]) -> anyhow::Result<Key> {
     if key == RAND_FLAG {
-        let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+        let key = ChaCha20Poly1305::generate_key(&mut utils::rng());
         return Ok(key);
     }
     if key.len() < KEY_LENGTH {
@@ -6,7 +6,8 @@ use super::{
 };
 use anyhow::anyhow;
 use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
-use rand::rngs::OsRng;
+
+use crate::utils;
 
 pub struct Ed25519Signer {
     key: SigningKey,
@@ -29,8 +30,7 @@ impl KeyGenerator for Ed25519Signer {
     fn generate() -> anyhow::Result<Vec<Vec<u8>>> {
         let mut res = vec![];
 
-        let mut cspring = OsRng;
-        let private_key: SigningKey = SigningKey::generate(&mut cspring);
+        let private_key: SigningKey = SigningKey::generate(&mut utils::rng());
         let public_key: VerifyingKey = private_key.verifying_key();
 
         res.push(private_key.as_bytes().to_vec());
@@ -1,26 +1,755 @@
-use anyhow::{anyhow, Ok};
-use chrono::{prelude::Local, TimeZone};
-use colored::*;
+use anyhow::anyhow;
+use chrono::{
+    prelude::Local, DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime,
+    SubsecRound, TimeZone, Utc, Weekday,
+};
+use chrono_tz::Tz;
 
-pub fn process_unix_to_string(unix: i64) -> anyhow::Result<()> {
+use crate::cli::{DurationUnit, TimeFormat, TimeUnit};
+
+/// The instant of the unix timestamp, `0` meaning now, and the unit it's in. The unit is
+/// detected from the magnitude if not given, and `None` for now.
+pub fn process_unix_to_datetime(
+    unix: i64,
+    unit: Option<TimeUnit>,
+) -> anyhow::Result<(DateTime<Utc>, Option<TimeUnit>)> {
     if unix == 0 {
-        let now = Local::now();
-        println!(
-            "{}: {}",
-            now.timestamp().to_string().yellow(),
-            now.format("%Y-%m-%d %H:%M:%S %Z").to_string().purple()
+        return Ok((Utc::now().trunc_subsecs(0), None));
+    }
+    let unit = unit.unwrap_or_else(|| detect_unit(unix));
+    let t = unix_to_datetime(unix, unit).ok_or_else(|| anyhow!("invalid unix: {}", unix))?;
+    Ok((t, Some(unit)))
+}
+
+/// Guess the unit of a timestamp from its magnitude: seconds up to 10^11, which is in the
+/// year 5138, then milliseconds, microseconds and nanoseconds for each 1000 times more.
+pub fn detect_unit(unix: i64) -> TimeUnit {
+    match unix.unsigned_abs() {
+        n if n < 100_000_000_000 => TimeUnit::Seconds,
+        n if n < 100_000_000_000_000 => TimeUnit::Millis,
+        n if n < 100_000_000_000_000_000 => TimeUnit::Micros,
+        _ => TimeUnit::Nanos,
+    }
+}
+
+pub fn unix_to_datetime(unix: i64, unit: TimeUnit) -> Option<DateTime<Utc>> {
+    match unit {
+        TimeUnit::Seconds => DateTime::from_timestamp(unix, 0),
+        TimeUnit::Millis => DateTime::from_timestamp_millis(unix),
+        TimeUnit::Micros => DateTime::from_timestamp_micros(unix),
+        TimeUnit::Nanos => Some(DateTime::from_timestamp_nanos(unix)),
+    }
+}
+
+/// Format the instant like `2024-05-01 10:00:00 +08:00 CST`, the abbreviation is only
+/// shown for IANA zones that have one.
+pub fn format_in_zone(t: &DateTime<Utc>, zone: Option<Tz>) -> String {
+    const FORMAT: &str = "%Y-%m-%d %H:%M:%S%.f %:z";
+    let Some(zone) = zone else {
+        return t.with_timezone(&Local).format(FORMAT).to_string();
+    };
+    let t = t.with_timezone(&zone);
+    let abbr = t.format("%Z").to_string();
+    match abbr.starts_with(['+', '-']) {
+        true => t.format(FORMAT).to_string(),
+        false => format!("{} {}", t.format(FORMAT), abbr),
+    }
+}
+
+/// One line per zone with the instant in that zone, offsets follow daylight saving time.
+pub fn format_zones(t: &DateTime<Utc>, zones: &[Tz], format: Option<&TimeFormat>) -> String {
+    let width = zones.iter().map(|tz| tz.name().len()).max().unwrap_or(0);
+    zones
+        .iter()
+        .map(|tz| {
+            let time = match format {
+                Some(format) => format_time(&to_zone(t, Some(*tz)), format),
+                None => format_in_zone(t, Some(*tz)),
+            };
+            format!("{:width$}  {}\n", tz.name(), time)
+        })
+        .collect()
+}
+
+/// The instant in the zone, or the local one if `None`.
+pub fn to_zone(t: &DateTime<Utc>, zone: Option<Tz>) -> DateTime<FixedOffset> {
+    match zone {
+        Some(zone) => t.with_timezone(&zone).fixed_offset(),
+        None => t.with_timezone(&Local).fixed_offset(),
+    }
+}
+
+pub fn format_time(t: &DateTime<FixedOffset>, format: &TimeFormat) -> String {
+    match format {
+        TimeFormat::Rfc3339 => t.to_rfc3339(),
+        // `to_rfc2822` panics on years it can't represent
+        TimeFormat::Rfc2822 => t.format("%a, %d %b %Y %H:%M:%S %z").to_string(),
+        TimeFormat::IsoWeek => t.format("%G-W%V-%u").to_string(),
+        TimeFormat::Http => t.to_utc().format("%a, %d %b %Y %H:%M:%S GMT").to_string(),
+        TimeFormat::Unix => t.timestamp().to_string(),
+        TimeFormat::Excel => excel_serial(t).to_string(),
+        TimeFormat::Custom(pattern) => t.format(pattern).to_string(),
+    }
+}
+
+/// Every preset format at once, with the time in each of `zones` as RFC 3339.
+pub fn time_json(t: &DateTime<FixedOffset>, zones: &[Tz]) -> serde_json::Value {
+    let mut json = serde_json::json!({
+        "unix": t.timestamp(),
+        "millis": t.timestamp_millis(),
+        "rfc3339": format_time(t, &TimeFormat::Rfc3339),
+        "rfc2822": format_time(t, &TimeFormat::Rfc2822),
+        "iso_week": format_time(t, &TimeFormat::IsoWeek),
+        "http": format_time(t, &TimeFormat::Http),
+        "excel": excel_serial(t),
+    });
+    if !zones.is_empty() {
+        let utc = t.to_utc();
+        json["zones"] = zones
+            .iter()
+            .map(|tz| {
+                let time = format_time(&to_zone(&utc, Some(*tz)), &TimeFormat::Rfc3339);
+                (tz.name().to_string(), serde_json::Value::String(time))
+            })
+            .collect();
+    }
+    json
+}
+
+/// Days since 1899-12-30 in local time, the date serial of spreadsheets.
+fn excel_serial(t: &DateTime<FixedOffset>) -> f64 {
+    let epoch = NaiveDate::from_ymd_opt(1899, 12, 30)
+        .expect("1899-12-30 is a valid date")
+        .and_time(NaiveTime::MIN);
+    let days = (t.naive_local() - epoch).num_milliseconds() as f64 / 86_400_000.0;
+    (days * 1e8).round() / 1e8
+}
+
+/// Naive formats, in the local time zone.
+const NAIVE_FORMATS: &[&str] = &[
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%dT%H:%M",
+    "%Y/%m/%d %H:%M:%S",
+    "%Y/%m/%d %H:%M",
+    "%d/%b/%Y:%H:%M:%S",
+    "%a %b %e %H:%M:%S %Y",
+    "%b %e %Y %H:%M:%S",
+];
+
+/// Formats with an offset like `+0800` or `+08:00`.
+const OFFSET_FORMATS: &[&str] = &[
+    "%Y-%m-%d %H:%M:%S%.f %z",
+    "%Y-%m-%dT%H:%M:%S%.f%z",
+    "%Y-%m-%d %H:%M %z",
+    "%Y/%m/%d %H:%M:%S %z",
+    "%d/%b/%Y:%H:%M:%S %z",
+];
+
+/// Parse the date, see [`parse_datetime`]. Dates without an offset are in the zone, or the
+/// local one if `None`, and the result is converted to the zone.
+pub fn process_time_parse(input: &str, zone: Option<Tz>) -> anyhow::Result<DateTime<FixedOffset>> {
+    match zone {
+        Some(zone) => {
+            let t = parse_datetime(input, &Utc::now().with_timezone(&zone))?;
+            Ok(t.with_timezone(&zone).fixed_offset())
+        }
+        None => parse_datetime(input, &Local::now()),
+    }
+}
+
+/// Parse a unix timestamp in any unit like [`process_unix_to_datetime`], so `0` is now,
+/// or a date like [`process_time_parse`].
+pub fn process_time_input(input: &str, zone: Option<Tz>) -> anyhow::Result<DateTime<FixedOffset>> {
+    if let Ok(unix) = input.trim().parse::<i64>() {
+        let (t, _) = process_unix_to_datetime(unix, None)?;
+        return Ok(to_zone(&t, zone));
+    }
+    process_time_parse(input, zone)
+}
+
+/// Add the duration to a time read by [`process_time_input`], it may be negative.
+pub fn process_time_add(
+    input: &str,
+    duration: Duration,
+    zone: Option<Tz>,
+) -> anyhow::Result<DateTime<FixedOffset>> {
+    process_time_input(input, zone)?
+        .checked_add_signed(duration)
+        .ok_or_else(|| anyhow!("the time is out of range"))
+}
+
+/// The duration from `from` to `to`, negative if `to` is earlier.
+pub fn process_time_diff(from: &str, to: &str, zone: Option<Tz>) -> anyhow::Result<Duration> {
+    Ok(process_time_input(to, zone)? - process_time_input(from, zone)?)
+}
+
+/// The duration as a number of the unit, with a fraction if it isn't a whole number of them.
+pub fn convert_duration(d: Duration, unit: DurationUnit) -> f64 {
+    let unit_millis = match unit {
+        DurationUnit::Millis => 1,
+        DurationUnit::Seconds => 1000,
+        DurationUnit::Minutes => 60 * 1000,
+        DurationUnit::Hours => 60 * 60 * 1000,
+        DurationUnit::Days => 24 * 60 * 60 * 1000,
+        DurationUnit::Weeks => 7 * 24 * 60 * 60 * 1000,
+    };
+    d.num_milliseconds() as f64 / unit_millis as f64
+}
+
+/// Parse a date like `2024-05-01 10:00 +0800`, RFC 3339, RFC 2822, common log formats,
+/// `@1714528800`, or relative to `now` like `yesterday 9am` and `3 days ago`. Dates
+/// without an offset are in the time zone of `now`.
+pub fn parse_datetime<Tz: TimeZone>(
+    input: &str,
+    now: &DateTime<Tz>,
+) -> anyhow::Result<DateTime<FixedOffset>> {
+    let input = input.trim();
+    if let Some(secs) = input.strip_prefix('@') {
+        let secs: i64 = secs.parse()?;
+        return DateTime::from_timestamp(secs, 0)
+            .map(|t| t.fixed_offset())
+            .ok_or_else(|| anyhow!("invalid unix: {}", secs));
+    }
+    if let Ok(t) = DateTime::parse_from_rfc3339(input) {
+        return Ok(t);
+    }
+    if let Ok(t) = DateTime::parse_from_rfc2822(input) {
+        return Ok(t);
+    }
+    // `UTC` and `GMT` are the only zone names with a well-known offset
+    let utc = ["UTC", "GMT", "Z"]
+        .iter()
+        .find_map(|zone| input.strip_suffix(zone))
+        .map(|rest| format!("{} +0000", rest.trim_end()));
+    let with_offset = utc.as_deref().unwrap_or(input);
+    for fmt in OFFSET_FORMATS {
+        if let Ok(t) = DateTime::parse_from_str(with_offset, fmt) {
+            return Ok(t);
+        }
+    }
+    let tz = now.timezone();
+    for fmt in NAIVE_FORMATS {
+        if let Ok(t) = NaiveDateTime::parse_from_str(input, fmt) {
+            return from_local(&tz, t);
+        }
+    }
+    for fmt in ["%Y-%m-%d", "%Y/%m/%d"] {
+        if let Ok(d) = NaiveDate::parse_from_str(input, fmt) {
+            return from_local(&tz, d.and_time(NaiveTime::MIN));
+        }
+    }
+    // syslog, like `May  1 10:00:00`, is in the current year
+    let syslog = format!("{} {}", now.year(), input);
+    if let Ok(t) = NaiveDateTime::parse_from_str(&syslog, "%Y %b %e %H:%M:%S") {
+        return from_local(&tz, t);
+    }
+    parse_relative(input, now)
+}
+
+/// Parse `now`, a day like `yesterday` or `last monday` with an optional time like `9am`
+/// before or after it, a bare time, or `3 days ago` and `in 1 hour 30 minutes`.
+fn parse_relative<Tz: TimeZone>(
+    input: &str,
+    now: &DateTime<Tz>,
+) -> anyhow::Result<DateTime<FixedOffset>> {
+    let input = input.to_lowercase();
+    let words: Vec<_> = input
+        .split_whitespace()
+        .filter(|word| *word != "at")
+        .collect();
+    let invalid = || anyhow!("unrecognized date: {}", input);
+    if words == ["now"] {
+        return Ok(now.fixed_offset());
+    }
+    let today = now.date_naive();
+    let day = (1..=words.len().min(2)).find_map(|n| {
+        let (head, tail) = words.split_at(n);
+        let (rest, day) = words.split_at(words.len() - n);
+        parse_day(head, today)
+            .map(|date| (date, tail))
+            .or_else(|| parse_day(day, today).map(|date| (date, rest)))
+    });
+    if let Some((date, clock)) = day {
+        let time = match clock {
+            [] => NaiveTime::MIN,
+            clock => parse_clock(&clock.concat()).ok_or_else(invalid)?,
+        };
+        return from_local(&now.timezone(), date.and_time(time));
+    }
+    if let Some(time) = parse_clock(&words.concat()) {
+        return from_local(&now.timezone(), today.and_time(time));
+    }
+    let t = match words.as_slice() {
+        ["in", amount @ ..] => now.clone() + parse_amount(amount).ok_or_else(invalid)?,
+        [amount @ .., "ago"] => now.clone() - parse_amount(amount).ok_or_else(invalid)?,
+        _ => return Err(invalid()),
+    };
+    Ok(t.fixed_offset())
+}
+
+/// Parse `today`, `yesterday`, `tomorrow`, or `last` and `next` with a weekday, which
+/// are the closest one strictly before or after today.
+fn parse_day(words: &[&str], today: NaiveDate) -> Option<NaiveDate> {
+    let days_from = |weekday: Weekday| {
+        today.weekday().num_days_from_monday() as i64 - weekday.num_days_from_monday() as i64
+    };
+    let days = match words {
+        ["today"] => 0,
+        ["yesterday"] => -1,
+        ["tomorrow"] => 1,
+        ["last", weekday] => -((days_from(weekday.parse().ok()?) + 6).rem_euclid(7) + 1),
+        ["next", weekday] => (-days_from(weekday.parse().ok()?) + 6).rem_euclid(7) + 1,
+        _ => return None,
+    };
+    today.checked_add_signed(Duration::days(days))
+}
+
+/// Parse a time of day like `9am`, `9:30pm`, `21:00`, `21:00:15`, `noon` or `midnight`.
+fn parse_clock(s: &str) -> Option<NaiveTime> {
+    match s {
+        "noon" => return NaiveTime::from_hms_opt(12, 0, 0),
+        "midnight" => return Some(NaiveTime::MIN),
+        _ => {}
+    }
+    let (clock, meridiem) = match (s.strip_suffix("am"), s.strip_suffix("pm")) {
+        (Some(clock), _) => (clock, Some(0)),
+        (_, Some(clock)) => (clock, Some(12)),
+        _ => (s, None),
+    };
+    let mut parts = clock.split(':');
+    let mut hour: u32 = parts.next()?.parse().ok()?;
+    let min: u32 = parts.next().map_or(Some(0), |m| m.parse().ok())?;
+    let sec: u32 = parts.next().map_or(Some(0), |s| s.parse().ok())?;
+    if parts.next().is_some() || (meridiem.is_none() && !clock.contains(':')) {
+        return None;
+    }
+    if let Some(offset) = meridiem {
+        if !(1..=12).contains(&hour) {
+            return None;
+        }
+        hour = hour % 12 + offset;
+    }
+    NaiveTime::from_hms_opt(hour, min, sec)
+}
+
+/// Parse an amount of time like `3 days`, `1 hour 30 minutes`, `1h and 30m` or `2h`.
+fn parse_amount(words: &[&str]) -> Option<Duration> {
+    let joined: String = words
+        .iter()
+        .filter(|word| **word != "and")
+        .copied()
+        .collect();
+    if joined.is_empty() {
+        return None;
+    }
+    let mut rest = joined.as_str();
+    let mut total = Duration::zero();
+    while !rest.is_empty() {
+        let split = rest.find(|c: char| !c.is_ascii_digit())?;
+        let (n, tail) = rest.split_at(split);
+        let split = tail
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(tail.len());
+        let (unit, tail) = tail.split_at(split);
+        let n: i64 = n.parse().ok()?;
+        let millis = match unit.trim_end_matches(',') {
+            "ms" | "msec" | "msecs" | "millisecond" | "milliseconds" => 1,
+            "s" | "sec" | "secs" | "second" | "seconds" => 1000,
+            "m" | "min" | "mins" | "minute" | "minutes" => 60 * 1000,
+            "h" | "hr" | "hrs" | "hour" | "hours" => 60 * 60 * 1000,
+            "d" | "day" | "days" => 24 * 60 * 60 * 1000,
+            "w" | "week" | "weeks" => 7 * 24 * 60 * 60 * 1000,
+            _ => return None,
+        };
+        total = total.checked_add(&Duration::try_milliseconds(n.checked_mul(millis)?)?)?;
+        rest = tail;
+    }
+    Some(total)
+}
+
+/// Parse a duration like `1d2h`, `90m`, `3600` in seconds, or ISO 8601 like `P1DT2H`, with an
+/// optional sign. Compact units are `w`, `d`, `h`, `m` and `s`, a trailing number is seconds.
+pub fn parse_duration(input: &str) -> anyhow::Result<Duration> {
+    let input = input.trim();
+    let (negative, rest) = match input.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, input.strip_prefix('+').unwrap_or(input)),
+    };
+    let d = match rest.strip_prefix(['P', 'p']) {
+        Some(iso) => parse_iso_duration(iso)?,
+        None => parse_compact_duration(rest)?,
+    };
+    Ok(if negative { -d } else { d })
+}
+
+/// Format a duration in ISO 8601 like `P1DT2H30M`, days are the largest unit.
+pub fn format_iso_duration(d: Duration) -> String {
+    let sign = if d < Duration::zero() { "-" } else { "" };
+    let [days, hours, mins, secs, millis] = split_duration(d.abs());
+    let mut res = format!("{sign}P");
+    if days > 0 {
+        res += &format!("{days}D");
+    }
+    if hours + mins + secs + millis > 0 {
+        res.push('T');
+        if hours > 0 {
+            res += &format!("{hours}H");
+        }
+        if mins > 0 {
+            res += &format!("{mins}M");
+        }
+        match millis {
+            0 if secs > 0 => res += &format!("{secs}S"),
+            0 => {}
+            millis => res += &format!("{secs}.{}S", format!("{millis:03}").trim_end_matches('0')),
+        }
+    }
+    match res.len() == sign.len() + 1 {
+        true => "PT0S".to_string(),
+        false => res,
+    }
+}
+
+/// Format a duration for people, like `2 days 3 hours 4 minutes`.
+pub fn humanize_duration(d: Duration) -> String {
+    const UNITS: [&str; 5] = ["day", "hour", "minute", "second", "millisecond"];
+    let parts: Vec<_> = split_duration(d.abs())
+        .iter()
+        .zip(UNITS)
+        .filter(|(n, _)| **n > 0)
+        .map(|(n, unit)| match n {
+            1 => format!("1 {unit}"),
+            n => format!("{n} {unit}s"),
+        })
+        .collect();
+    match (parts.is_empty(), d < Duration::zero()) {
+        (true, _) => "0 seconds".to_string(),
+        (false, true) => format!("-{}", parts.join(" ")),
+        (false, false) => parts.join(" "),
+    }
+}
+
+/// Split a non-negative duration into days, hours, minutes, seconds and milliseconds.
+fn split_duration(d: Duration) -> [i64; 5] {
+    let millis = d.num_milliseconds();
+    [
+        millis / 86_400_000,
+        millis / 3_600_000 % 24,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        millis % 1000,
+    ]
+}
+
+fn parse_compact_duration(input: &str) -> anyhow::Result<Duration> {
+    let invalid = || anyhow!("invalid duration: {}", input);
+    let mut secs: i64 = 0;
+    let mut number = String::new();
+    for c in input.to_lowercase().chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            'w' => 7 * 24 * 60 * 60,
+            _ => return Err(invalid()),
+        };
+        let n: i64 = number.parse().map_err(|_| invalid())?;
+        secs = n
+            .checked_mul(unit)
+            .and_then(|n| secs.checked_add(n))
+            .ok_or_else(invalid)?;
+        number.clear();
+    }
+    if !number.is_empty() {
+        let n: i64 = number.parse().map_err(|_| invalid())?;
+        secs = secs.checked_add(n).ok_or_else(invalid)?;
+    }
+    Duration::try_seconds(secs).ok_or_else(invalid)
+}
+
+/// Parse the part of an ISO 8601 duration after `P`. Years and months are rejected as their
+/// length depends on the date, and only seconds may have a fraction.
+fn parse_iso_duration(iso: &str) -> anyhow::Result<Duration> {
+    let invalid = || anyhow!("invalid ISO 8601 duration: P{}", iso);
+    let (date, time) = match iso.split_once(['T', 't']) {
+        Some((_, "")) => return Err(invalid()),
+        Some((date, time)) => (date, time),
+        None => (iso, ""),
+    };
+    if date.is_empty() && time.is_empty() {
+        return Err(invalid());
+    }
+    let mut millis: i64 = 0;
+    for (part, is_time) in [(date, false), (time, true)] {
+        let mut number = String::new();
+        for c in part.chars() {
+            if c.is_ascii_digit() || c == '.' || c == ',' {
+                number.push(c);
+                continue;
+            }
+            let unit: i64 = match (c.to_ascii_uppercase(), is_time) {
+                ('W', false) => 7 * 24 * 60 * 60 * 1000,
+                ('D', false) => 24 * 60 * 60 * 1000,
+                ('H', true) => 60 * 60 * 1000,
+                ('M', true) => 60 * 1000,
+                ('S', true) => 1000,
+                ('Y' | 'M', false) => {
+                    return Err(anyhow!("years and months have no fixed length, use days"))
+                }
+                _ => return Err(invalid()),
+            };
+            let n = match c.to_ascii_uppercase() {
+                'S' => {
+                    let n: f64 = number.replace(',', ".").parse().map_err(|_| invalid())?;
+                    (n * unit as f64).round() as i64
+                }
+                _ => {
+                    let n: i64 = number.parse().map_err(|_| invalid())?;
+                    n.checked_mul(unit).ok_or_else(invalid)?
+                }
+            };
+            millis = millis.checked_add(n).ok_or_else(invalid)?;
+            number.clear();
+        }
+        if !number.is_empty() {
+            return Err(invalid());
+        }
+    }
+    Duration::try_milliseconds(millis).ok_or_else(invalid)
+}
+
+/// Resolve a local time in the time zone, the earlier one when clocks go back.
+fn from_local<Tz: TimeZone>(tz: &Tz, t: NaiveDateTime) -> anyhow::Result<DateTime<FixedOffset>> {
+    tz.from_local_datetime(&t)
+        .earliest()
+        .map(|t| t.fixed_offset())
+        .ok_or_else(|| anyhow!("{} doesn't exist in the time zone", t))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::SecondsFormat;
+
+    fn now() -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339("2024-05-01T15:30:00+08:00").unwrap()
+    }
+
+    #[test]
+    fn test_parse_datetime() -> anyhow::Result<()> {
+        let cases = [
+            ("2024-05-01 10:00 +0800", "2024-05-01T10:00:00+08:00"),
+            ("2024-05-01T10:00:00.5Z", "2024-05-01T10:00:00.500+00:00"),
+            ("Wed, 01 May 2024 02:00:00 GMT", "2024-05-01T02:00:00+00:00"),
+            ("2024-05-01 02:00:00 UTC", "2024-05-01T02:00:00+00:00"),
+            ("01/May/2024:10:00:00 -0700", "2024-05-01T10:00:00-07:00"),
+            ("2024/05/01 10:00:00", "2024-05-01T10:00:00+08:00"),
+            ("Wed May  1 10:00:00 2024", "2024-05-01T10:00:00+08:00"),
+            ("May  1 10:00:00", "2024-05-01T10:00:00+08:00"),
+            ("2024-05-01", "2024-05-01T00:00:00+08:00"),
+            ("@1714528800", "2024-05-01T02:00:00+00:00"),
+        ];
+        for (input, expected) in cases {
+            let t = parse_datetime(input, &now())?;
+            assert_eq!(
+                t.to_rfc3339_opts(SecondsFormat::AutoSi, false),
+                expected,
+                "{input}"
+            );
+        }
+        assert!(parse_datetime("2024-13-01", &now()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_relative() -> anyhow::Result<()> {
+        let cases = [
+            ("now", "2024-05-01T15:30:00+08:00"),
+            ("today", "2024-05-01T00:00:00+08:00"),
+            ("yesterday 9am", "2024-04-30T09:00:00+08:00"),
+            ("Tomorrow at 9:30 pm", "2024-05-02T21:30:00+08:00"),
+            ("12am", "2024-05-01T00:00:00+08:00"),
+            ("18:45", "2024-05-01T18:45:00+08:00"),
+            ("3 days ago", "2024-04-28T15:30:00+08:00"),
+            ("in 2h", "2024-05-01T17:30:00+08:00"),
+            ("in 500ms", "2024-05-01T15:30:00.500+08:00"),
+            ("in 5 mins", "2024-05-01T15:35:00+08:00"),
+            ("in 1 hour 30 minutes", "2024-05-01T17:00:00+08:00"),
+            ("1h and 30m ago", "2024-05-01T14:00:00+08:00"),
+            ("2 days, 3 hours ago", "2024-04-29T12:30:00+08:00"),
+            ("10am tomorrow", "2024-05-02T10:00:00+08:00"),
+            ("noon yesterday", "2024-04-30T12:00:00+08:00"),
+            ("last monday", "2024-04-29T00:00:00+08:00"),
+            ("last wednesday", "2024-04-24T00:00:00+08:00"),
+            ("next Mon 9am", "2024-05-06T09:00:00+08:00"),
+            ("9am next wednesday", "2024-05-08T09:00:00+08:00"),
+            ("next friday", "2024-05-03T00:00:00+08:00"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                parse_datetime(input, &now())?.to_rfc3339(),
+                expected,
+                "{input}"
+            );
+        }
+        assert!(parse_datetime("yesterday 13pm", &now()).is_err());
+        assert!(parse_datetime("3 fortnights ago", &now()).is_err());
+        assert!(parse_datetime("in 5 hourss", &now()).is_err());
+        assert!(parse_datetime("in 1 hour 30", &now()).is_err());
+        assert!(parse_datetime("last someday", &now()).is_err());
+        assert!(parse_datetime("9am tomorrow 10am", &now()).is_err());
+
+        // dates without an offset are in the zone of `now`, across a DST change
+        let ny = Utc::now().with_timezone(&chrono_tz::America::New_York);
+        assert_eq!(
+            parse_datetime("2024-01-15 09:00", &ny)?.to_rfc3339(),
+            "2024-01-15T09:00:00-05:00"
+        );
+        assert_eq!(
+            parse_datetime("2024-07-15 09:00", &ny)?.to_rfc3339(),
+            "2024-07-15T09:00:00-04:00"
         );
-        return Ok(());
+        Ok(())
+    }
+
+    #[test]
+    fn test_unix_to_datetime() {
+        let cases = [
+            (1714528800, TimeUnit::Seconds),
+            (1714528800123, TimeUnit::Millis),
+            (1714528800123456, TimeUnit::Micros),
+            (1714528800123456789, TimeUnit::Nanos),
+            (-86400, TimeUnit::Seconds),
+            (-86400000, TimeUnit::Seconds),
+            (-8640000000000, TimeUnit::Millis),
+        ];
+        for (unix, unit) in cases {
+            assert_eq!(detect_unit(unix), unit, "{unix}");
+        }
+        let t = unix_to_datetime(1714528800123, TimeUnit::Millis).unwrap();
+        assert_eq!(
+            format_in_zone(&t, Some(chrono_tz::UTC)),
+            "2024-05-01 02:00:00.123 +00:00 UTC"
+        );
+        let t = unix_to_datetime(-86400, TimeUnit::Seconds).unwrap();
+        assert_eq!(t.to_rfc3339(), "1969-12-31T00:00:00+00:00");
+        assert!(unix_to_datetime(i64::MAX, TimeUnit::Seconds).is_none());
+    }
+
+    #[test]
+    fn test_format_zones() {
+        let winter = DateTime::from_timestamp(1704067200, 0).unwrap();
+        let summer = DateTime::from_timestamp(1719792000, 0).unwrap();
+        let zones = [
+            chrono_tz::Asia::Shanghai,
+            chrono_tz::Europe::London,
+            chrono_tz::UTC,
+        ];
+        assert_eq!(
+            format_zones(&winter, &zones, None),
+            "Asia/Shanghai  2024-01-01 08:00:00 +08:00 CST\n\
+             Europe/London  2024-01-01 00:00:00 +00:00 GMT\n\
+             UTC            2024-01-01 00:00:00 +00:00 UTC\n"
+        );
+        assert_eq!(
+            format_in_zone(&summer, Some(chrono_tz::Europe::London)),
+            "2024-07-01 01:00:00 +01:00 BST"
+        );
+    }
+
+    #[test]
+    fn test_parse_duration() -> anyhow::Result<()> {
+        let cases = [
+            ("90", 90),
+            ("1s100m1h", 1 + 100 * 60 + 3600),
+            ("1w2D", 9 * 86400),
+            ("-3d", -3 * 86400),
+            ("P1DT2H", 86400 + 7200),
+            ("PT1.5S", 1),
+            ("P2W", 14 * 86400),
+            ("-PT30M", -1800),
+        ];
+        for (input, secs) in cases {
+            assert_eq!(parse_duration(input)?.num_seconds(), secs, "{input}");
+        }
+        assert_eq!(parse_duration("PT1.5S")?.num_milliseconds(), 1500);
+        for input in [
+            "1x",
+            "P1Y",
+            "P1M",
+            "P",
+            "PT",
+            "P1",
+            "P1H",
+            "99999999999999w",
+        ] {
+            assert!(parse_duration(input).is_err(), "{input}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_duration() -> anyhow::Result<()> {
+        let d = Duration::seconds(2 * 86400 + 3 * 3600 + 4 * 60 + 5);
+        assert_eq!(format_iso_duration(d), "P2DT3H4M5S");
+        assert_eq!(humanize_duration(d), "2 days 3 hours 4 minutes 5 seconds");
+        assert_eq!(format_iso_duration(-Duration::hours(1)), "-PT1H");
+        assert_eq!(humanize_duration(-Duration::hours(1)), "-1 hour");
+        assert_eq!(format_iso_duration(Duration::milliseconds(1500)), "PT1.5S");
+        assert_eq!(format_iso_duration(Duration::days(1)), "P1D");
+        assert_eq!(format_iso_duration(Duration::zero()), "PT0S");
+        assert_eq!(humanize_duration(Duration::zero()), "0 seconds");
+        let d = parse_duration("P1DT2H30M")?;
+        assert_eq!(parse_duration(&format_iso_duration(d))?, d);
+        assert_eq!(convert_duration(d, DurationUnit::Hours), 26.5);
+        assert_eq!(convert_duration(d, DurationUnit::Minutes), 1590.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_time_diff() -> anyhow::Result<()> {
+        let utc = Some(chrono_tz::UTC);
+        let d = process_time_diff("2024-05-01T00:00:00Z", "1714528800000", utc)?;
+        assert_eq!(d, Duration::hours(2));
+        let d = process_time_diff("2024-05-01 10:00 +0800", "2024-04-30", utc)?;
+        assert_eq!(d, Duration::hours(-26));
+        let t = process_time_add("2024-05-01T00:00:00Z", parse_duration("P1DT2H")?, utc)?;
+        assert_eq!(t.to_rfc3339(), "2024-05-02T02:00:00+00:00");
+
+        // `0` is now, like in `rcli time`
+        let d = process_time_diff("0", "now", utc)?;
+        assert!(d >= Duration::zero() && d < Duration::seconds(5));
+        Ok(())
     }
 
-    let t = Local.timestamp_opt(unix, 0).single();
-    if t.is_none() {
-        return Err(anyhow!(format!("invalid unix: {}", unix)));
+    #[test]
+    fn test_format_time() {
+        let t = DateTime::parse_from_rfc3339("2024-05-01T10:00:00+08:00").unwrap();
+        let cases = [
+            (TimeFormat::Rfc3339, "2024-05-01T10:00:00+08:00"),
+            (TimeFormat::Rfc2822, "Wed, 01 May 2024 10:00:00 +0800"),
+            (TimeFormat::IsoWeek, "2024-W18-3"),
+            (TimeFormat::Http, "Wed, 01 May 2024 02:00:00 GMT"),
+            (TimeFormat::Unix, "1714528800"),
+            (TimeFormat::Excel, "45413.41666667"),
+            (
+                TimeFormat::Custom("%d.%m.%Y %H:%M".into()),
+                "01.05.2024 10:00",
+            ),
+        ];
+        for (format, expected) in cases {
+            assert_eq!(format_time(&t, &format), expected, "{format:?}");
+        }
+        let json = time_json(&t, &[chrono_tz::UTC]);
+        assert_eq!(json["millis"], 1714528800000i64);
+        assert_eq!(json["excel"], 45413.41666667);
+        assert_eq!(json["zones"]["UTC"], "2024-05-01T02:00:00+00:00");
     }
-    println!(
-        "{}: {}",
-        unix.to_string().yellow(),
-        t.unwrap().to_string().purple()
-    );
-    Ok(())
 }
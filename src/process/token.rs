@@ -0,0 +1,87 @@
+use anyhow::anyhow;
+use rand::seq::SliceRandom;
+
+use super::codec::BASE62;
+use crate::utils;
+
+/// A CRC32 takes at most 6 base62 characters.
+const CHECKSUM_LENGTH: usize = 6;
+
+/// Generate a token made of `prefix`, `length` random base62 characters and a
+/// base62 CRC32 checksum of everything before it, like GitHub tokens.
+pub fn process_gen_token(prefix: &str, length: u8) -> anyhow::Result<String> {
+    if let Some(c) = prefix
+        .chars()
+        .find(|c| !c.is_ascii_alphanumeric() && *c != '_')
+    {
+        return Err(anyhow!(
+            "invalid token prefix, `{c}` is not an ASCII letter, digit or `_`"
+        ));
+    }
+
+    let mut rng = utils::rng();
+    let mut token = prefix.to_string();
+    for _ in 0..length {
+        let c = BASE62.choose(&mut rng).expect("BASE62 won't be empty");
+        token.push(*c as char);
+    }
+    let checksum = checksum(&token);
+    token.push_str(&checksum);
+    Ok(token)
+}
+
+/// Check the embedded checksum of the token offline.
+pub fn process_verify_token(token: &str) -> bool {
+    if !token.is_ascii() || token.len() <= CHECKSUM_LENGTH {
+        return false;
+    }
+    let (body, expected) = token.split_at(token.len() - CHECKSUM_LENGTH);
+    checksum(body) == expected
+}
+
+/// The BLAKE3 hash of the token in hex, to store instead of the token itself.
+pub fn token_hash(token: &str) -> String {
+    blake3::hash(token.as_bytes()).to_hex().to_string()
+}
+
+fn checksum(body: &str) -> String {
+    let mut n = crc32fast::hash(body.as_bytes());
+    let mut res = [BASE62[0]; CHECKSUM_LENGTH];
+    for c in res.iter_mut().rev() {
+        *c = BASE62[(n % 62) as usize];
+        n /= 62;
+    }
+    String::from_utf8_lossy(&res).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_process_gen_token() -> anyhow::Result<()> {
+        let token = process_gen_token("rcli_live_", 30)?;
+        assert!(token.starts_with("rcli_live_"));
+        assert_eq!(token.len(), 10 + 30 + CHECKSUM_LENGTH);
+        assert!(process_verify_token(&token));
+        assert!(process_gen_token("rcli-live", 30).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_verify_token() -> anyhow::Result<()> {
+        let token = process_gen_token("rcli_test_", 30)?;
+        let mut tampered = token.clone().into_bytes();
+        tampered[12] = if tampered[12] == b'a' { b'b' } else { b'a' };
+        assert!(!process_verify_token(&String::from_utf8(tampered)?));
+        assert!(!process_verify_token("short"));
+        assert!(!process_verify_token("rcli_live_ü12345"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_checksum() {
+        assert_eq!(checksum(""), "000000");
+        assert_eq!(checksum("a").len(), CHECKSUM_LENGTH);
+    }
+}
@@ -1,6 +1,18 @@
-use std::{fs::File, io::Read};
+use std::{
+    fs::File,
+    io::{IsTerminal, Read, Write},
+    sync::{Mutex, OnceLock},
+};
 
 use colored::Colorize;
+use rand::{rngs::OsRng, CryptoRng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+use crate::process::hex::{hexdump, HexDumpOptions};
+
+/// The generator seeded by `--seed`, all random values of the process are drawn from it,
+/// whichever thread of the runtime they are drawn on.
+static SEEDED_RNG: OnceLock<Mutex<Option<ChaCha20Rng>>> = OnceLock::new();
 
 pub fn get_reader(input: &str) -> anyhow::Result<Box<dyn Read>> {
     let reader: Box<dyn Read> = match input {
@@ -10,6 +22,27 @@ pub fn get_reader(input: &str) -> anyhow::Result<Box<dyn Read>> {
     Ok(reader)
 }
 
+/// The writer of binary output: the `output` file, or stdout if it isn't a terminal.
+/// `None` means stdout is a terminal and the bytes should be rendered with [`print_bytes`].
+pub fn get_binary_writer(output: Option<&str>) -> anyhow::Result<Option<Box<dyn Write>>> {
+    let writer: Box<dyn Write> = match output {
+        Some(filename) => Box::new(File::create(filename)?),
+        None if std::io::stdout().is_terminal() => return Ok(None),
+        None => Box::new(std::io::stdout().lock()),
+    };
+    Ok(Some(writer))
+}
+
+/// Print bytes to a terminal, as text if they are printable UTF-8, otherwise as a hexdump.
+pub fn print_bytes(data: &[u8]) {
+    match std::str::from_utf8(data) {
+        Ok(text) if !text.chars().any(|c| c.is_control() && !c.is_whitespace()) => {
+            println!("{}", text.trim_end_matches('\n'));
+        }
+        _ => print!("{}", hexdump(data, &HexDumpOptions::default())),
+    }
+}
+
 pub fn print_verify_result(res: bool) {
     if res {
         println!("\n{}", "√ Signature verified".green())
@@ -17,3 +50,65 @@ pub fn print_verify_result(res: bool) {
         println!("\n{}", "x Signature not verified".red())
     }
 }
+
+/// Read a secret line from stdin, prompting for it without echo if stdin is a terminal.
+pub fn read_secret(prompt: &str) -> anyhow::Result<String> {
+    if std::io::stdin().is_terminal() {
+        return Ok(rpassword::prompt_password(prompt)?);
+    }
+    let mut line = String::new();
+    std::io::stdin().read_line(&mut line)?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// INSECURE: make every random value of the process, e.g. keys, nonces,
+/// salts and passwords, reproducible from `seed`. Only meant for tests.
+pub fn set_insecure_seed(seed: u64) {
+    *seeded_rng() = Some(ChaCha20Rng::seed_from_u64(seed));
+}
+
+fn seeded_rng() -> std::sync::MutexGuard<'static, Option<ChaCha20Rng>> {
+    SEEDED_RNG
+        .get_or_init(|| Mutex::new(None))
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+/// The random number generator every command draws from. It's the OS CSPRNG,
+/// unless [`set_insecure_seed`] was called.
+pub fn rng() -> RcliRng {
+    RcliRng
+}
+
+/// See [`rng`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RcliRng;
+
+impl RcliRng {
+    fn with<T>(f: impl FnOnce(&mut dyn RngCore) -> T) -> T {
+        match seeded_rng().as_mut() {
+            Some(rng) => f(rng),
+            None => f(&mut OsRng),
+        }
+    }
+}
+
+impl RngCore for RcliRng {
+    fn next_u32(&mut self) -> u32 {
+        Self::with(|rng| rng.next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        Self::with(|rng| rng.next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        Self::with(|rng| rng.fill_bytes(dest))
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        Self::with(|rng| rng.try_fill_bytes(dest))
+    }
+}
+
+impl CryptoRng for RcliRng {}
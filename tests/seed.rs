@@ -0,0 +1,27 @@
+//! The seed of `--seed` is process-wide, so these checks run in their own test binary
+//! where no other test draws random values in parallel.
+
+use rand::RngCore;
+use rcli::{process::gen_pass::process_genpass, utils};
+
+#[test]
+fn test_insecure_seed() -> anyhow::Result<()> {
+    let mut a = [0u8; 32];
+    let mut b = [0u8; 32];
+    utils::set_insecure_seed(42);
+    utils::rng().fill_bytes(&mut a);
+    utils::set_insecure_seed(42);
+    utils::rng().fill_bytes(&mut b);
+    assert_eq!(a, b);
+
+    utils::rng().fill_bytes(&mut b);
+    assert_ne!(a, b);
+
+    // drawn on another thread, like a worker of the runtime or `spawn_blocking`
+    utils::set_insecure_seed(7);
+    let a = std::thread::spawn(|| process_genpass(16, true, true, true, true)).join();
+    utils::set_insecure_seed(7);
+    let b = process_genpass(16, true, true, true, true)?;
+    assert_eq!(a.expect("thread won't panic")?, b);
+    Ok(())
+}